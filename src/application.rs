@@ -0,0 +1,174 @@
+use crate::*;
+use crate::events::emit_event;
+use crate::roles::Role;
+
+pub(crate) const DEFAULT_APPLICATION_BOND: Balance = 1_000_000_000_000_000_000_000_000;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ApplicationStatus {
+    Pending,
+    /// whitelisted, the bond is held
+    Active,
+    /// left the whitelist, the bond can be claimed once the timelock delay passed
+    Left,
+    Slashed
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Application {
+    pub applicant_id: AccountId,
+    pub metadata: SourceMetadata,
+    pub bond: U128,
+    pub status: ApplicationStatus,
+    pub applied_at: U64,
+    pub left_at: Option<U64>
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Called by the source contract or its parent account, the attached deposit is held as a bond
+    /// while it is whitelisted.
+    #[payable]
+    pub fn apply_for_whitelist(&mut self, contract_id: AccountId, metadata: SourceMetadata) {
+        self.assert_source_or_parent(&contract_id);
+        assert!(env::attached_deposit() >= self.application_bond, "bond too small");
+        metadata.assert_valid();
+        assert!(!self.white_list.contains(&contract_id), "already whitelisted");
+        assert!(self.revocations.get(&contract_id).is_none(), "revoked");
+        assert!(self.applications.get(&contract_id).is_none(), "application exists");
+        let application = Application {
+            applicant_id: env::predecessor_account_id(),
+            metadata,
+            bond: env::attached_deposit().into(),
+            status: ApplicationStatus::Pending,
+            applied_at: env::block_timestamp().into(),
+            left_at: None
+        };
+        self.applications.insert(&contract_id, &application);
+        emit_event("application_submit", json!({
+            "contract_id": contract_id,
+            "application": application
+        }));
+    }
+
+    pub fn approve_application(&mut self, contract_id: AccountId) {
+        self.assert_role(Role::WhitelistManager);
+        let mut application = self.applications.get(&contract_id).expect("application not found");
+        assert!(application.status == ApplicationStatus::Pending, "not pending");
+        application.status = ApplicationStatus::Active;
+        self.white_list.insert(contract_id.clone());
+        self.source_metadata.insert(&contract_id, &application.metadata);
+        self.applications.insert(&contract_id, &application);
+        emit_event("application_approve", json!({
+            "contract_id": contract_id,
+            "sender_id": env::predecessor_account_id()
+        }));
+    }
+
+    /// Refunds the bond.
+    pub fn reject_application(&mut self, contract_id: AccountId, reason: String) {
+        self.assert_role(Role::WhitelistManager);
+        let application = self.applications.get(&contract_id).expect("application not found");
+        assert!(application.status == ApplicationStatus::Pending, "not pending");
+        self.applications.remove(&contract_id);
+        Promise::new(application.applicant_id).transfer(application.bond.0);
+        emit_event("application_reject", json!({
+            "contract_id": contract_id,
+            "reason": reason,
+            "sender_id": env::predecessor_account_id()
+        }));
+    }
+
+    /// Called by the source or its parent account, leaves the whitelist right away.
+    pub fn leave_whitelist(&mut self, contract_id: AccountId) {
+        self.assert_source_or_parent(&contract_id);
+        let mut application = self.applications.get(&contract_id).expect("application not found");
+        assert!(application.status == ApplicationStatus::Active, "not active");
+        self.white_list.remove(&contract_id);
+        application.status = ApplicationStatus::Left;
+        application.left_at = Some(env::block_timestamp().into());
+        self.applications.insert(&contract_id, &application);
+        emit_event("whitelist_leave", json!({
+            "contract_id": contract_id
+        }));
+    }
+
+    /// Refunds the bond once the source left without being revoked for the timelock delay.
+    pub fn claim_bond(&mut self, contract_id: AccountId) {
+        let application = self.applications.get(&contract_id).expect("application not found");
+        assert!(application.status == ApplicationStatus::Left, "not left");
+        assert!(env::block_timestamp() >= application.left_at.unwrap().0 + self.timelock_delay, "bond still locked");
+        self.applications.remove(&contract_id);
+        Promise::new(application.applicant_id.clone()).transfer(application.bond.0);
+        emit_event("application_bond_refund", json!({
+            "contract_id": contract_id,
+            "applicant_id": application.applicant_id,
+            "bond": application.bond
+        }));
+    }
+
+    /// Sends the bond kept from a revoked source to `receiver_id`.
+    pub fn withdraw_slashed_bond(&mut self, contract_id: AccountId, receiver_id: AccountId) {
+        self.assert_owner();
+        let application = self.applications.get(&contract_id).expect("application not found");
+        assert!(application.status == ApplicationStatus::Slashed, "not slashed");
+        self.applications.remove(&contract_id);
+        Promise::new(receiver_id.clone()).transfer(application.bond.0);
+        emit_event("application_bond_withdraw", json!({
+            "contract_id": contract_id,
+            "receiver_id": receiver_id,
+            "bond": application.bond
+        }));
+    }
+
+    pub fn set_application_bond(&mut self, application_bond: U128) {
+        self.assert_owner();
+        self.application_bond = application_bond.0;
+    }
+
+    pub fn get_application_bond(&self) -> U128 {
+        self.application_bond.into()
+    }
+
+    pub fn get_application(&self, contract_id: AccountId) -> Option<Application> {
+        self.applications.get(&contract_id)
+    }
+
+    pub fn get_applications(&self, from_index: u64, limit: u64) -> Vec<(AccountId, Application)> {
+        self.applications.iter().skip(from_index as usize).take(limit as usize).collect()
+    }
+}
+
+impl Contract {
+    /// Contracts deployed without access keys apply through the account that created them.
+    fn assert_source_or_parent(&self, contract_id: &AccountId) {
+        let sender_id = env::predecessor_account_id();
+        assert!(&sender_id == contract_id || get_parent_id(contract_id) == Some(sender_id), "not source or parent");
+    }
+
+    /// Keeps the bond of an abusive source, otherwise refunds it.
+    pub(crate) fn internal_settle_bond(&mut self, contract_id: &AccountId, slash_bond: bool) {
+        let mut application = match self.applications.get(contract_id) {
+            Some(application) if application.status != ApplicationStatus::Slashed => application,
+            _ => return
+        };
+        if slash_bond {
+            application.status = ApplicationStatus::Slashed;
+            self.applications.insert(contract_id, &application);
+            emit_event("application_bond_slash", json!({
+                "contract_id": contract_id,
+                "bond": application.bond
+            }));
+            return
+        }
+        self.applications.remove(contract_id);
+        Promise::new(application.applicant_id.clone()).transfer(application.bond.0);
+        emit_event("application_bond_refund", json!({
+            "contract_id": contract_id,
+            "applicant_id": application.applicant_id,
+            "bond": application.bond
+        }));
+    }
+}
@@ -0,0 +1,58 @@
+use crate::*;
+use crate::events::emit_event;
+use crate::roles::Role;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Ban {
+    pub reason: String,
+    pub banned_at: U64,
+    pub moderator_id: AccountId,
+    /// also blocks burning, consuming, locking and vouching the current balance
+    pub freeze: bool
+}
+
+#[near_bindgen]
+impl Contract {
+    /// A freeze also takes back the voting power the account delegated.
+    pub fn ban_account(&mut self, account_id: AccountId, reason: String, freeze: bool) {
+        self.assert_role(Role::Moderator);
+        if freeze {
+            self.internal_delegate(&account_id, &account_id);
+        }
+        let ban = Ban {
+            reason,
+            banned_at: env::block_timestamp().into(),
+            moderator_id: env::predecessor_account_id(),
+            freeze
+        };
+        self.bans.insert(&account_id, &ban);
+        emit_event("account_ban", json!({
+            "account_id": account_id,
+            "ban": ban
+        }));
+    }
+
+    pub fn unban_account(&mut self, account_id: AccountId) {
+        self.assert_role(Role::Moderator);
+        self.bans.remove(&account_id).expect("not banned");
+        emit_event("account_unban", json!({
+            "account_id": account_id,
+            "moderator_id": env::predecessor_account_id()
+        }));
+    }
+
+    pub fn get_ban(&self, account_id: AccountId) -> Option<Ban> {
+        self.bans.get(&account_id)
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_is_banned(&self, account_id: &AccountId) -> bool {
+        self.bans.get(account_id).is_some()
+    }
+
+    pub(crate) fn assert_not_frozen(&self, account_id: &AccountId) {
+        assert!(!self.bans.get(account_id).map(|ban| ban.freeze).unwrap_or(false), "account frozen");
+    }
+}
@@ -0,0 +1,44 @@
+use near_sdk::assert_one_yocto;
+
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Burns the caller's own drip, from one source or from any sources when `source_id` is None.
+    #[payable]
+    pub fn ft_burn(&mut self, source_id: Option<AccountId>, amount: U128) {
+        assert_one_yocto();
+        self.assert_not_paused(self.pause_flags.burn);
+        let sender_id = env::predecessor_account_id();
+        self.internal_assert_available(&sender_id, amount.0);
+        let contract_ids = match source_id {
+            Some(source_id) => vec![source_id],
+            None => self.internal_get_drip_sources(&sender_id)
+        };
+        self.internal_burn_from_sources(&sender_id, contract_ids, amount.0, json!({
+            "self_burn": true
+        }));
+    }
+}
+
+impl Contract {
+    /// Zeroes every per-source balance ahead of a forced unregistration.
+    pub(crate) fn internal_reset_account(&mut self, account_id: &AccountId) {
+        for lock_id in self.account_locks.get(account_id).unwrap_or_default() {
+            match self.lock_vouches.get(&lock_id) {
+                Some(vouch_id) => self.internal_remove_vouch(vouch_id),
+                None => self.internal_remove_lock(lock_id)
+            }
+        }
+        for contract_id in self.internal_get_drip_sources(account_id) {
+            let balance = self.internal_get_drip(account_id, &contract_id);
+            if balance > 0 {
+                self.internal_burn(account_id, balance, &contract_id, Some(json!({
+                    "contract_id": contract_id,
+                    "unregister": true
+                }).to_string()));
+            }
+        }
+        self.delegates.remove(account_id);
+    }
+}
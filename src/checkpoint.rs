@@ -0,0 +1,52 @@
+use near_sdk::collections::Vector;
+
+use crate::*;
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Checkpoint {
+    pub block_height: u64,
+    pub timestamp: u64,
+    pub balance: Balance
+}
+
+pub(crate) fn push_checkpoint(checkpoints: &mut Vector<Checkpoint>, old_balance: Balance, new_balance: Balance) {
+    // balances earned before tracking started are recorded as if held since genesis
+    if checkpoints.len() == 0 && old_balance > 0 {
+        checkpoints.push(&Checkpoint {
+            block_height: 0,
+            timestamp: 0,
+            balance: old_balance
+        });
+    }
+    let checkpoint = Checkpoint {
+        block_height: env::block_height(),
+        timestamp: env::block_timestamp(),
+        balance: new_balance
+    };
+    let len = checkpoints.len();
+    if len > 0 && checkpoints.get(len - 1).unwrap().timestamp == checkpoint.timestamp {
+        checkpoints.replace(len - 1, &checkpoint);
+    } else {
+        checkpoints.push(&checkpoint);
+    }
+}
+
+/// None if nothing has been recorded yet, in which case the current balance applies.
+pub(crate) fn get_checkpoint_balance(checkpoints: &Vector<Checkpoint>, timestamp: u64) -> Option<Balance> {
+    if checkpoints.len() == 0 {
+        return None
+    }
+    let (mut low, mut high) = (0, checkpoints.len());
+    while low < high {
+        let mid = (low + high) / 2;
+        if checkpoints.get(mid).unwrap().timestamp > timestamp {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+    match low {
+        0 => Some(0),
+        _ => Some(checkpoints.get(low - 1).unwrap().balance)
+    }
+}
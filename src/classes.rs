@@ -0,0 +1,142 @@
+use crate::*;
+use crate::events::emit_event;
+use crate::roles::Role;
+
+/// An extra reputation dimension hosted next to the default DRIP token.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct TokenClass {
+    pub token: FungibleToken,
+    pub metadata: FungibleTokenMetadata,
+    pub white_list: HashSet<AccountId>,
+    pub supply_cap: Option<Balance>
+}
+
+/// Registers the collector in `token` if needed and checks the deposit covers the new sources.
+/// Returns the collector's available storage balance from before the call.
+pub(crate) fn internal_register_collector(token: &mut FungibleToken, sender_id: &AccountId, collects: &Vec<AccountId>) -> Balance {
+    let storage_balance = match token.storage_balance_of(sender_id.clone()) {
+        Some(v) => v.available.0,
+        None => {
+            assert!(token.account_storage_usage as u128 * env::storage_byte_cost() < env::attached_deposit(), "not registered");
+            token.internal_register_account(sender_id);
+            0
+        }
+    };
+
+    let account = token.accounts.get(sender_id).unwrap();
+    let unregister_count = collects.iter().filter(|contract_id| account.is_registered(contract_id) == false).count() as u128;
+    assert!(token.account_storage_usage as u128 * env::storage_byte_cost() * unregister_count <= env::attached_deposit() + storage_balance, "not enough deposit");
+    storage_balance
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn create_class(&mut self, class_id: String, metadata: FungibleTokenMetadata, supply_cap: Option<U128>) {
+        self.assert_owner();
+        assert!(self.classes.get(&class_id).is_none(), "class exists");
+        metadata.assert_valid();
+        let prefix = [b"T".as_slice(), &env::sha256(class_id.as_bytes())].concat();
+        self.classes.insert(&class_id, &TokenClass {
+            token: FungibleToken::new(prefix),
+            metadata,
+            white_list: HashSet::new(),
+            supply_cap: supply_cap.map(|cap| cap.0)
+        });
+        emit_event("class_create", json!({
+            "class_id": class_id
+        }));
+    }
+
+    /// Removals are queued behind the timelock.
+    pub fn set_class_white_list(&mut self, class_id: String, contract_id: AccountId, del: bool) {
+        self.assert_role(Role::WhitelistManager);
+        let mut class = self.classes.get(&class_id).expect("class not found");
+        if del {
+            self.internal_queue_action(AdminAction::RemoveFromClassWhiteList { class_id, contract_id });
+            return
+        }
+        class.white_list.insert(contract_id);
+        self.classes.insert(&class_id, &class);
+    }
+
+    /// Queues a cap on the total supply of a class, the default DRIP token when `class_id` is None.
+    pub fn set_class_cap(&mut self, class_id: Option<String>, supply_cap: Option<U128>) -> u64 {
+        self.internal_queue_action(AdminAction::SetClassCap { class_id, supply_cap })
+    }
+
+    pub fn get_class_cap(&self, class_id: Option<String>) -> Option<U128> {
+        let supply_cap = match class_id {
+            Some(class_id) => self.classes.get(&class_id).expect("class not found").supply_cap,
+            None => self.supply_cap
+        };
+        supply_cap.map(|cap| cap.into())
+    }
+
+    pub fn get_classes(&self) -> Vec<String> {
+        self.classes.keys().collect()
+    }
+
+    pub fn class_ft_metadata(&self, class_id: String) -> FungibleTokenMetadata {
+        self.classes.get(&class_id).expect("class not found").metadata
+    }
+
+    pub fn class_ft_balance_of(&self, class_id: String, account_id: AccountId) -> U128 {
+        self.classes.get(&class_id).expect("class not found").token.ft_balance_of(account_id)
+    }
+
+    pub fn class_ft_total_supply(&self, class_id: String) -> U128 {
+        self.classes.get(&class_id).expect("class not found").token.ft_total_supply()
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_is_class_source(&self, class_id: &Option<String>, contract_id: &AccountId) -> bool {
+        match class_id {
+            Some(class_id) => {
+                let class = self.classes.get(class_id).expect("class not found");
+                self.revocations.get(contract_id).is_none() && !self.suspended_sources.contains_key(contract_id) && (
+                    get_root_id(contract_id.clone()) == get_root_id(env::current_account_id()) || class.white_list.contains(contract_id)
+                )
+            },
+            None => self.internal_is_source(contract_id)
+        }
+    }
+
+    pub(crate) fn internal_set_class_cap(&mut self, class_id: Option<String>, supply_cap: Option<Balance>) {
+        match &class_id {
+            Some(id) => {
+                let mut class = self.classes.get(id).expect("class not found");
+                class.supply_cap = supply_cap;
+                self.classes.insert(id, &class);
+            },
+            None => self.supply_cap = supply_cap
+        }
+        emit_event("class_cap_update", json!({
+            "class_id": class_id,
+            "supply_cap": supply_cap.map(U128)
+        }));
+    }
+
+    /// Classes other than the default one emit their own event so NEP-141 indexers only see DRIP.
+    pub(crate) fn internal_set_class_drip(&mut self, balance: u128, contract_id: AccountId, account_id: AccountId, class_id: &String) {
+        if !self.internal_is_class_source(&Some(class_id.clone()), &contract_id) || self.internal_is_banned(&account_id) {
+            return
+        }
+        let mut class = self.classes.get(class_id).unwrap();
+        let balance = match class.supply_cap {
+            Some(cap) => std::cmp::min(balance, cap.saturating_sub(class.token.ft_total_supply().0)),
+            None => balance
+        };
+        if balance == 0 {
+            return
+        }
+        class.token.internal_deposit(&account_id, balance, &contract_id);
+        self.classes.insert(class_id, &class);
+        emit_event("class_mint", json!({
+            "class_id": class_id,
+            "owner_id": account_id,
+            "amount": U128(balance),
+            "contract_id": contract_id
+        }));
+    }
+}
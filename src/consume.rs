@@ -0,0 +1,70 @@
+use crate::*;
+use crate::events::emit_event;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Approval {
+    /// sources the consumer may burn from, any source when None
+    pub source_filter: Option<Vec<AccountId>>,
+    pub allowance: U128,
+    pub expires_at: Option<U64>
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Allows `consumer_id` to burn up to `max_amount` of the caller's drip, a zero amount removes the approval.
+    /// The attached deposit pays for the approval's storage, the rest is refunded.
+    #[payable]
+    pub fn drip_approve(&mut self, consumer_id: AccountId, source_filter: Option<Vec<AccountId>>, max_amount: U128, expires_at: Option<U64>) {
+        assert!(env::attached_deposit() > 0, "requires attached deposit");
+        let initial_storage_usage = env::storage_usage();
+        let sender_id = env::predecessor_account_id();
+        let key = (sender_id.clone(), consumer_id.clone());
+        let approval = Approval {
+            source_filter,
+            allowance: max_amount,
+            expires_at
+        };
+        match max_amount.0 {
+            0 => self.approvals.remove(&key),
+            _ => self.approvals.insert(&key, &approval)
+        };
+        emit_event("drip_approve", json!({
+            "owner_id": sender_id,
+            "consumer_id": consumer_id,
+            "approval": approval
+        }));
+        refund_deposit(initial_storage_usage);
+    }
+
+    pub fn drip_consume(&mut self, account_id: AccountId, amount: U128, memo: Option<String>) {
+        self.assert_not_paused(self.pause_flags.burn);
+        let consumer_id = env::predecessor_account_id();
+        let key = (account_id.clone(), consumer_id.clone());
+        let mut approval = self.approvals.get(&key).expect("not approved");
+        if let Some(expires_at) = approval.expires_at {
+            assert!(env::block_timestamp() < expires_at.0, "approval expired");
+        }
+        assert!(approval.allowance.0 >= amount.0, "exceeds allowance");
+        self.internal_assert_available(&account_id, amount.0);
+
+        let contract_ids = approval.source_filter.clone().unwrap_or_else(|| self.internal_get_drip_sources(&account_id));
+        self.internal_burn_from_sources(&account_id, contract_ids, amount.0, json!({
+            "consumer_id": consumer_id,
+            "memo": memo
+        }));
+        approval.allowance = (approval.allowance.0 - amount.0).into();
+        self.approvals.insert(&key, &approval);
+        emit_event("drip_consume", json!({
+            "owner_id": account_id,
+            "consumer_id": consumer_id,
+            "amount": amount,
+            "remaining_allowance": approval.allowance,
+            "memo": memo
+        }));
+    }
+
+    pub fn get_drip_approval(&self, account_id: AccountId, consumer_id: AccountId) -> Option<Approval> {
+        self.approvals.get(&(account_id, consumer_id))
+    }
+}
@@ -0,0 +1,185 @@
+use crate::*;
+use crate::events::emit_event;
+
+const MAX_COUNCIL_SIZE: usize = 32;
+const OWNER_CALL_GAS: u64 = 50_000_000_000_000;
+const ON_OWNER_CALL_GAS: u64 = 10_000_000_000_000;
+/// owner-only methods an `OwnerCall` may reach, everything else has its own proposal kind
+const OWNER_CALL_METHODS: [&str; 7] = [
+    "create_class",
+    "set_tier_thresholds",
+    "set_application_bond",
+    "withdraw_slashed_bond",
+    "propose_owner",
+    "cancel_owner_proposal",
+    "renounce_ownership"
+];
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Council {
+    pub members: Vec<AccountId>,
+    /// approvals needed before a proposal executes
+    pub threshold: u32
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ProposalKind {
+    /// removals still go through the timelock once approved
+    SetWhiteList { contract_id: AccountId, del: bool },
+    SetPauseFlags { pause_flags: PauseFlags },
+    QueueAction { action: AdminAction },
+    /// None disbands the council and hands the owner methods back to the owner
+    SetCouncil { council: Option<Council> },
+    GrantRole { role: Role, account_id: AccountId },
+    RevokeRole { role: Role, account_id: AccountId },
+    /// calls one of `OWNER_CALL_METHODS`, args are its JSON arguments
+    OwnerCall { method_name: String, args: String, deposit: U128 }
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Proposal {
+    pub proposer_id: AccountId,
+    pub kind: ProposalKind,
+    pub description: String,
+    pub approvals: Vec<AccountId>,
+    pub created_at: U64,
+    pub executed: bool
+}
+
+#[near_bindgen]
+impl Contract {
+    /// While a council is set it holds the owner methods, so this only goes through a proposal.
+    pub fn set_council(&mut self, council: Option<Council>) {
+        self.assert_owner();
+        self.internal_set_council(council);
+    }
+
+    pub fn get_council(&self) -> Option<Council> {
+        self.council.clone()
+    }
+
+    /// The proposer's approval is counted right away, returns the proposal id.
+    pub fn propose(&mut self, kind: ProposalKind, description: String) -> u64 {
+        self.assert_council_member();
+        if let ProposalKind::OwnerCall { method_name, .. } = &kind {
+            assert!(OWNER_CALL_METHODS.contains(&method_name.as_str()), "not an owner method");
+        }
+        let proposal_id = self.proposals.len();
+        self.proposals.push(&Proposal {
+            proposer_id: env::predecessor_account_id(),
+            kind,
+            description,
+            approvals: vec![],
+            created_at: env::block_timestamp().into(),
+            executed: false
+        });
+        emit_event("proposal_create", json!({
+            "proposal_id": proposal_id,
+            "proposer_id": env::predecessor_account_id()
+        }));
+        self.approve_proposal(proposal_id);
+        proposal_id
+    }
+
+    /// Executes the proposal once enough current members approved it.
+    pub fn approve_proposal(&mut self, proposal_id: u64) {
+        self.assert_council_member();
+        let sender_id = env::predecessor_account_id();
+        let mut proposal = self.proposals.get(proposal_id).expect("proposal not found");
+        assert!(!proposal.executed, "already executed");
+        assert!(!proposal.approvals.contains(&sender_id), "already approved");
+        proposal.approvals.push(sender_id.clone());
+        emit_event("proposal_approve", json!({
+            "proposal_id": proposal_id,
+            "account_id": sender_id
+        }));
+
+        let council = self.council.clone().unwrap();
+        let approval_count = proposal.approvals.iter().filter(|account_id| council.members.contains(account_id)).count();
+        if approval_count >= council.threshold as usize {
+            proposal.executed = true;
+            self.internal_execute_proposal(proposal_id, proposal.kind.clone());
+            emit_event("proposal_execute", json!({
+                "proposal_id": proposal_id,
+                "kind": proposal.kind
+            }));
+        }
+        self.proposals.replace(proposal_id, &proposal);
+    }
+
+    /// A failed `OwnerCall` reopens its proposal with no approvals, so members can approve it again to retry.
+    #[private]
+    pub fn on_owner_call(&mut self, proposal_id: u64) -> bool {
+        let success = matches!(env::promise_result(0), near_sdk::PromiseResult::Successful(_));
+        if !success {
+            let mut proposal = self.proposals.get(proposal_id).expect("proposal not found");
+            proposal.executed = false;
+            proposal.approvals = vec![];
+            self.proposals.replace(proposal_id, &proposal);
+        }
+        emit_event("proposal_call", json!({
+            "proposal_id": proposal_id,
+            "success": success
+        }));
+        success
+    }
+
+    pub fn get_proposal(&self, proposal_id: u64) -> Option<Proposal> {
+        self.proposals.get(proposal_id)
+    }
+
+    pub fn get_proposals(&self, from_index: u64, limit: u64) -> Vec<(u64, Proposal)> {
+        (from_index..std::cmp::min(from_index + limit, self.proposals.len()))
+            .map(|proposal_id| (proposal_id, self.proposals.get(proposal_id).unwrap()))
+            .collect()
+    }
+}
+
+impl Contract {
+    fn internal_execute_proposal(&mut self, proposal_id: u64, kind: ProposalKind) {
+        let council_id = env::current_account_id();
+        match kind {
+            ProposalKind::SetWhiteList { contract_id, del: true } => {
+                self.internal_schedule_action(AdminAction::RemoveFromWhiteList { contract_id }, council_id);
+            },
+            ProposalKind::SetWhiteList { contract_id, del: false } => {
+                self.white_list.insert(contract_id);
+            },
+            ProposalKind::SetPauseFlags { pause_flags } => self.internal_set_pause_flags(pause_flags),
+            ProposalKind::QueueAction { action } => {
+                self.internal_schedule_action(action, council_id);
+            },
+            ProposalKind::SetCouncil { council } => self.internal_set_council(council),
+            ProposalKind::GrantRole { role, account_id } => self.internal_grant_role(role, account_id),
+            ProposalKind::RevokeRole { role, account_id } => self.internal_revoke_role(role, account_id),
+            ProposalKind::OwnerCall { method_name, args, deposit } => {
+                Promise::new(council_id.clone())
+                    .function_call(method_name, args.into_bytes(), deposit.0, Gas(OWNER_CALL_GAS))
+                    .then(Promise::new(council_id).function_call("on_owner_call".to_string(), json!({
+                        "proposal_id": proposal_id
+                    }).to_string().into_bytes(), 0, Gas(ON_OWNER_CALL_GAS)));
+            }
+        }
+    }
+
+    fn internal_set_council(&mut self, council: Option<Council>) {
+        if let Some(council) = &council {
+            let members: HashSet<&AccountId> = council.members.iter().collect();
+            assert!(members.len() == council.members.len(), "duplicate member");
+            assert!(council.members.len() <= MAX_COUNCIL_SIZE, "too many members");
+            assert!(council.threshold > 0 && council.threshold as usize <= council.members.len(), "invalid threshold");
+        }
+        self.council = council.clone();
+        emit_event("council_update", json!({
+            "council": council
+        }));
+    }
+
+    fn assert_council_member(&self) {
+        let council = self.council.as_ref().expect("no council");
+        assert!(council.members.contains(&env::predecessor_account_id()), "not council member");
+    }
+}
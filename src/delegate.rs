@@ -0,0 +1,96 @@
+use crate::*;
+use crate::checkpoint::{push_checkpoint, get_checkpoint_balance};
+
+#[near_bindgen]
+impl Contract {
+    /// The attached deposit pays for the new vote checkpoints, the rest is refunded.
+    #[payable]
+    pub fn delegate(&mut self, delegatee: AccountId) {
+        let initial_storage_usage = env::storage_usage();
+        let sender_id = env::predecessor_account_id();
+        assert!(!self.internal_is_banned(&sender_id), "banned");
+        self.internal_delegate(&sender_id, &delegatee);
+        refund_deposit(initial_storage_usage);
+    }
+
+    #[payable]
+    pub fn undelegate(&mut self) {
+        let initial_storage_usage = env::storage_usage();
+        let sender_id = env::predecessor_account_id();
+        assert!(!self.internal_is_banned(&sender_id), "banned");
+        self.internal_delegate(&sender_id, &sender_id);
+        refund_deposit(initial_storage_usage);
+    }
+
+    pub fn get_delegate(&self, account_id: AccountId) -> Option<AccountId> {
+        self.delegates.get(&account_id)
+    }
+
+    pub fn get_votes(&self, account_id: AccountId) -> U128 {
+        self.internal_get_votes(&account_id).into()
+    }
+
+    pub fn get_votes_at(&self, account_id: AccountId, timestamp: U64) -> U128 {
+        let votes = self.vote_checkpoints.get(&account_id).and_then(|checkpoints| {
+            get_checkpoint_balance(&checkpoints, timestamp.0)
+        });
+        match votes {
+            Some(votes) => votes.into(),
+            None => self.internal_get_votes(&account_id).into()
+        }
+    }
+}
+
+impl Contract {
+    /// Accounts that never delegated vote with their own balance.
+    pub(crate) fn internal_get_votes(&self, account_id: &AccountId) -> Balance {
+        let latest = self.vote_checkpoints.get(account_id).and_then(|checkpoints| {
+            checkpoints.get(checkpoints.len().checked_sub(1)?).map(|checkpoint| checkpoint.balance)
+        });
+        match latest {
+            Some(votes) => votes,
+            None if self.delegates.get(account_id).is_none() => self.ft_balance_of(account_id.clone()).0,
+            None => 0
+        }
+    }
+
+    pub(crate) fn internal_get_delegatee(&self, account_id: &AccountId) -> AccountId {
+        self.delegates.get(account_id).unwrap_or(account_id.clone())
+    }
+
+    pub(crate) fn internal_set_votes(&mut self, account_id: &AccountId, old_votes: Balance, new_votes: Balance) {
+        let mut checkpoints = self.vote_checkpoints.get(account_id).unwrap_or_else(|| {
+            Vector::new(get_storage_key(b"V", account_id))
+        });
+        push_checkpoint(&mut checkpoints, old_votes, new_votes);
+        self.vote_checkpoints.insert(account_id, &checkpoints);
+    }
+
+    /// Moves the balance change of `account_id` onto whoever currently holds its voting power.
+    pub(crate) fn internal_move_votes(&mut self, account_id: &AccountId, old_balance: Balance, new_balance: Balance) {
+        let delegatee = self.internal_get_delegatee(account_id);
+        let old_votes = match &delegatee == account_id && self.vote_checkpoints.get(account_id).is_none() {
+            true => old_balance,
+            false => self.internal_get_votes(&delegatee)
+        };
+        let new_votes = (old_votes + new_balance).saturating_sub(old_balance);
+        self.internal_set_votes(&delegatee, old_votes, new_votes);
+    }
+
+    pub(crate) fn internal_delegate(&mut self, account_id: &AccountId, delegatee: &AccountId) {
+        let old_delegatee = self.internal_get_delegatee(account_id);
+        if &old_delegatee == delegatee {
+            return
+        }
+        let balance = self.ft_balance_of(account_id.clone()).0;
+
+        let old_votes = self.internal_get_votes(&old_delegatee);
+        self.internal_set_votes(&old_delegatee, old_votes, old_votes.saturating_sub(balance));
+        match delegatee == account_id {
+            true => self.delegates.remove(account_id),
+            false => self.delegates.insert(account_id, delegatee)
+        };
+        let new_votes = self.internal_get_votes(delegatee);
+        self.internal_set_votes(delegatee, new_votes, new_votes + balance);
+    }
+}
@@ -0,0 +1,13 @@
+use crate::*;
+
+const EVENT_STANDARD: &str = "drip";
+const EVENT_VERSION: &str = "1.0.0";
+
+pub(crate) fn emit_event(event: &str, data: serde_json::Value) {
+    log!("EVENT_JSON:{}", json!({
+        "standard": EVENT_STANDARD,
+        "version": EVENT_VERSION,
+        "event": event,
+        "data": [data]
+    }));
+}
@@ -0,0 +1,38 @@
+use crate::*;
+use crate::events::emit_event;
+use crate::roles::Role;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GasConfig {
+    pub this_function_call_gas: U64,
+    pub collect_drip_gas: U64,
+    pub resolve_collect_drip_gas_base: U64,
+    pub resolve_collect_drip_gas_x: U64
+}
+
+impl Default for GasConfig {
+    fn default() -> Self {
+        Self {
+            this_function_call_gas: THIS_FUNCTION_CALL_GAS.into(),
+            collect_drip_gas: COLLECT_DRIP_GAS.into(),
+            resolve_collect_drip_gas_base: RESOLVE_COLLECT_DRIP_GAS_BASE.into(),
+            resolve_collect_drip_gas_x: RESOLVE_COLLECT_DRIP_GAS_X.into()
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn set_gas_config(&mut self, gas_config: GasConfig) {
+        self.assert_role(Role::GasTuner);
+        self.gas_config = gas_config.clone();
+        emit_event("gas_config_update", json!({
+            "gas_config": gas_config
+        }));
+    }
+
+    pub fn get_gas_config(&self) -> GasConfig {
+        self.gas_config.clone()
+    }
+}
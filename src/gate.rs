@@ -0,0 +1,85 @@
+use crate::*;
+
+const ON_DRIP_VERIFIED_GAS_RESERVE: u64 = 10_000_000_000_000;
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub enum Requirement {
+    MinTotal { amount: U128 },
+    MinSource { source_id: AccountId, amount: U128 },
+    MinTier { tier: u32 }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct VerifiedBalances {
+    pub total: U128,
+    pub sources: HashMap<AccountId, U128>,
+    pub tier: u32
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Checks the caller against `requirement` and forwards the verified balances to
+    /// `receiver_id.on_drip_verified`, so that the receiver can trust the predecessor.
+    pub fn ft_gate_call(&mut self, receiver_id: AccountId, requirement: Requirement, msg: String) -> Promise {
+        let sender_id = env::predecessor_account_id();
+        assert!(!self.internal_is_banned(&sender_id), "banned");
+        let balances = self.internal_get_verified_balances(&sender_id);
+        let verified = match &requirement {
+            Requirement::MinTotal { amount } => balances.total.0 >= amount.0,
+            Requirement::MinSource { source_id, amount } => {
+                balances.sources.get(source_id).map(|balance| balance.0).unwrap_or(0) >= amount.0
+            },
+            Requirement::MinTier { tier } => balances.tier >= *tier
+        };
+        assert!(verified, "requirement not met");
+
+        Promise::new(receiver_id).function_call(
+            "on_drip_verified".to_string(),
+            json!({
+                "account_id": sender_id,
+                "balances": balances,
+                "msg": msg
+            }).to_string().into_bytes(),
+            0,
+            env::prepaid_gas() - env::used_gas() - Gas::from(ON_DRIP_VERIFIED_GAS_RESERVE)
+        )
+    }
+
+    /// Thresholds in ascending order, an account's tier is the number of thresholds it reaches.
+    pub fn set_tier_thresholds(&mut self, thresholds: Vec<U128>) {
+        self.assert_owner();
+        assert!(thresholds.windows(2).all(|pair| pair[0].0 < pair[1].0), "thresholds not ascending");
+        self.tier_thresholds = thresholds.into_iter().map(|threshold| threshold.0).collect();
+    }
+
+    pub fn get_tier_thresholds(&self) -> Vec<U128> {
+        self.tier_thresholds.iter().map(|threshold| (*threshold).into()).collect()
+    }
+
+    pub fn get_tier(&self, account_id: AccountId) -> u32 {
+        self.internal_get_tier(self.ft_balance_of(account_id).0)
+    }
+}
+
+impl Contract {
+    fn internal_get_tier(&self, balance: Balance) -> u32 {
+        self.tier_thresholds.iter().filter(|threshold| balance >= **threshold).count() as u32
+    }
+
+    fn internal_get_verified_balances(&self, account_id: &AccountId) -> VerifiedBalances {
+        let total = self.ft_balance_of(account_id.clone()).0;
+        let sources = self.internal_get_drip_sources(account_id).into_iter()
+            .map(|contract_id| {
+                let balance = self.internal_get_drip(account_id, &contract_id);
+                (contract_id, balance.into())
+            })
+            .collect();
+        VerifiedBalances {
+            total: total.into(),
+            sources,
+            tier: self.internal_get_tier(total)
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    pub fn get_holders(&self, from_index: u64, limit: u64) -> Vec<AccountId> {
+        self.holders.iter().skip(from_index as usize).take(limit as usize).collect()
+    }
+
+    pub fn get_source_holders(&self, contract_id: AccountId, from_index: u64, limit: u64) -> Vec<AccountId> {
+        match self.source_holders.get(&contract_id) {
+            Some(holders) => holders.iter().skip(from_index as usize).take(limit as usize).collect(),
+            None => vec![]
+        }
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_source_holder_count(&self, contract_id: &AccountId) -> u64 {
+        self.source_holders.get(contract_id).map(|holders| holders.len()).unwrap_or(0)
+    }
+
+    pub(crate) fn internal_update_source_holder(&mut self, account_id: &AccountId, contract_id: &AccountId, balance: Balance) {
+        let mut holders = self.source_holders.get(contract_id).unwrap_or_else(|| {
+            UnorderedSet::new(get_storage_key(b"H", contract_id))
+        });
+        let changed = match balance > 0 {
+            true => holders.insert(account_id),
+            false => holders.remove(account_id)
+        };
+        if changed {
+            self.source_holders.insert(contract_id, &holders);
+        }
+    }
+
+    pub(crate) fn internal_remove_holder(&mut self, account_id: &AccountId, contract_ids: Vec<AccountId>) {
+        self.holders.remove(account_id);
+        for contract_id in contract_ids {
+            self.internal_update_source_holder(account_id, &contract_id, 0);
+        }
+    }
+}
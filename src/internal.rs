@@ -1,21 +1,166 @@
 use std::collections::HashMap;
 
-use near_non_transferable_token::fungible_token::events::FtMint;
+use near_non_transferable_token::fungible_token::events::{FtMint, FtBurn};
 
 use crate::*;
+use crate::checkpoint::push_checkpoint;
+
+/// Balances captured before a change so that histories can be seeded and diffed afterwards.
+pub(crate) struct DripSnapshot {
+    pub balance: Balance,
+    pub source_balance: Balance,
+    pub supply: Balance
+}
 
 impl Contract {
     pub(crate) fn internal_set_drip(&mut self, balance: u128, contract_id: AccountId, account_id: AccountId) {
-        if get_root_id(contract_id.clone()) == get_root_id(env::current_account_id()) || self.white_list.get(&contract_id).is_some() {
-            self.token.internal_deposit(&account_id, balance, &contract_id);
-            FtMint {
-                owner_id: &account_id,
-                amount: &balance.into(),
-                memo: Some(&json!({
-                    "contract_id": contract_id
-                }).to_string()),
+        if self.internal_is_source(&contract_id) && !self.internal_is_banned(&account_id) {
+            let balance = self.internal_cap_mint(balance);
+            if balance == 0 {
+                return
+            }
+            self.internal_mint(&account_id, balance, &contract_id, json!({
+                "contract_id": contract_id
+            }).to_string());
+        }
+    }
+
+    /// Clamps `amount` to what the default supply cap still allows.
+    pub(crate) fn internal_cap_mint(&self, amount: Balance) -> Balance {
+        match self.supply_cap {
+            Some(cap) => std::cmp::min(amount, cap.saturating_sub(self.ft_total_supply().0)),
+            None => amount
+        }
+    }
+
+    pub(crate) fn internal_mint(&mut self, account_id: &AccountId, amount: Balance, contract_id: &AccountId, memo: String) {
+        let snapshot = self.internal_snapshot(account_id, contract_id);
+        self.token.internal_deposit(account_id, amount, contract_id);
+        self.internal_record_drip_change(account_id, contract_id, snapshot);
+        FtMint {
+            owner_id: account_id,
+            amount: &amount.into(),
+            memo: Some(&memo),
+        }
+        .emit();
+    }
+
+    pub(crate) fn internal_burn(&mut self, account_id: &AccountId, amount: Balance, contract_id: &AccountId, memo: Option<String>) {
+        let snapshot = self.internal_snapshot(account_id, contract_id);
+        self.token.internal_withdraw(account_id, amount, contract_id);
+        self.internal_record_drip_change(account_id, contract_id, snapshot);
+        self.internal_clamp_locks(account_id);
+        FtBurn {
+            owner_id: account_id,
+            amount: &amount.into(),
+            memo: memo.as_deref(),
+        }
+        .emit();
+    }
+
+    /// Burns `amount` spread over `contract_ids` in order, panics if they don't hold enough.
+    /// Returns how much was burned from each source.
+    pub(crate) fn internal_burn_from_sources(&mut self, account_id: &AccountId, contract_ids: Vec<AccountId>, amount: Balance, memo: serde_json::Value) -> Vec<(AccountId, Balance)> {
+        let mut burned = vec![];
+        let mut remaining = amount;
+        for contract_id in contract_ids {
+            if remaining == 0 {
+                break
+            }
+            let burn_amount = std::cmp::min(remaining, self.internal_get_drip(account_id, &contract_id));
+            if burn_amount == 0 {
+                continue
+            }
+            let mut memo = memo.clone();
+            memo["contract_id"] = json!(contract_id);
+            self.internal_burn(account_id, burn_amount, &contract_id, Some(memo.to_string()));
+            burned.push((contract_id, burn_amount));
+            remaining -= burn_amount;
+        }
+        assert!(remaining == 0, "not enough balance");
+        burned
+    }
+
+    pub(crate) fn internal_is_source(&self, contract_id: &AccountId) -> bool {
+        if self.revocations.get(contract_id).is_some() || self.suspended_sources.contains_key(contract_id) {
+            return false
+        }
+        get_root_id(contract_id.clone()) == get_root_id(env::current_account_id()) || self.white_list.get(contract_id).is_some()
+    }
+
+    pub(crate) fn internal_get_drip(&self, account_id: &AccountId, contract_id: &AccountId) -> Balance {
+        match self.token.accounts.get(account_id) {
+            Some(account) => account.get_balance(&Some(contract_id.clone())),
+            None => 0
+        }
+    }
+
+    pub(crate) fn internal_get_drip_sources(&self, account_id: &AccountId) -> Vec<AccountId> {
+        match self.token.accounts.get(account_id) {
+            Some(account) => account.contract_ids.keys().filter_map(|contract_id| contract_id).collect(),
+            None => vec![]
+        }
+    }
+
+    pub(crate) fn internal_snapshot(&self, account_id: &AccountId, contract_id: &AccountId) -> DripSnapshot {
+        DripSnapshot {
+            balance: self.ft_balance_of(account_id.clone()).0,
+            source_balance: self.internal_get_drip(account_id, contract_id),
+            supply: self.ft_total_supply().0
+        }
+    }
+
+    pub(crate) fn internal_snapshot_sources(&self, account_id: &AccountId) -> Vec<(AccountId, DripSnapshot)> {
+        self.internal_get_drip_sources(account_id).into_iter().map(|contract_id| {
+            let snapshot = self.internal_snapshot(account_id, &contract_id);
+            (contract_id, snapshot)
+        }).collect()
+    }
+
+    /// Records what a library call changed across several sources, the account totals are diffed only once.
+    pub(crate) fn internal_record_drip_changes(&mut self, account_id: &AccountId, snapshots: Vec<(AccountId, DripSnapshot)>) {
+        let mut account_changed = snapshots.first().map(|(_, snapshot)| {
+            snapshot.balance != self.ft_balance_of(account_id.clone()).0 || snapshot.supply != self.ft_total_supply().0
+        }).unwrap_or(false);
+        for (contract_id, mut snapshot) in snapshots {
+            if !account_changed {
+                if snapshot.source_balance == self.internal_get_drip(account_id, &contract_id) {
+                    continue
+                }
+                snapshot.balance = self.ft_balance_of(account_id.clone()).0;
+                snapshot.supply = self.ft_total_supply().0;
             }
-            .emit();
-        } 
+            self.internal_record_drip_change(account_id, &contract_id, snapshot);
+            account_changed = false;
+        }
+    }
+
+    /// Must be called after every balance change, including burns, to keep snapshots consistent.
+    pub(crate) fn internal_record_drip_change(&mut self, account_id: &AccountId, contract_id: &AccountId, snapshot: DripSnapshot) {
+        let mut checkpoints = self.account_checkpoints.get(account_id).unwrap_or_else(|| {
+            Vector::new(get_storage_key(b"C", account_id))
+        });
+        let new_balance = self.ft_balance_of(account_id.clone()).0;
+        push_checkpoint(&mut checkpoints, snapshot.balance, new_balance);
+        self.account_checkpoints.insert(account_id, &checkpoints);
+        self.internal_move_votes(account_id, snapshot.balance, new_balance);
+
+        let key = (account_id.clone(), contract_id.clone());
+        let mut checkpoints = self.source_checkpoints.get(&key).unwrap_or_else(|| {
+            Vector::new(get_pair_storage_key(b"S", account_id, contract_id))
+        });
+        let new_source_balance = self.internal_get_drip(account_id, contract_id);
+        push_checkpoint(&mut checkpoints, snapshot.source_balance, new_source_balance);
+        self.source_checkpoints.insert(&key, &checkpoints);
+        self.internal_update_source_holder(account_id, contract_id, new_source_balance);
+        self.internal_roll_up(account_id, contract_id, snapshot.source_balance, new_source_balance);
+        if new_balance > 0 {
+            self.holders.insert(account_id);
+        }
+        self.internal_update_leaderboard(None, account_id, new_balance);
+        self.internal_update_leaderboard(Some(contract_id.clone()), account_id, new_source_balance);
+
+        let new_supply = self.ft_total_supply().0;
+        push_checkpoint(&mut self.supply_checkpoints, snapshot.supply, new_supply);
     }
-}
\ No newline at end of file
+}
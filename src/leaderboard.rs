@@ -0,0 +1,41 @@
+use crate::*;
+
+const LEADERBOARD_SIZE: usize = 100;
+
+#[near_bindgen]
+impl Contract {
+    /// Top holders overall when `source_id` is None, otherwise top holders of drip from that source.
+    pub fn get_leaderboard(&self, source_id: Option<AccountId>, limit: u64) -> Vec<(AccountId, U128)> {
+        self.leaderboards.get(&source_id).unwrap_or_default().into_iter()
+            .take(limit as usize)
+            .map(|(account_id, balance)| (account_id, balance.into()))
+            .collect()
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_update_leaderboard(&mut self, source_id: Option<AccountId>, account_id: &AccountId, balance: Balance) {
+        let mut entries = self.leaderboards.get(&source_id).unwrap_or_default();
+        if update_entries(&mut entries, account_id, balance) {
+            self.leaderboards.insert(&source_id, &entries);
+        }
+    }
+}
+
+/// Keeps `entries` sorted by balance descending and bounded by `LEADERBOARD_SIZE`.
+/// Accounts pushed out of the board only come back on their next balance change.
+fn update_entries(entries: &mut Vec<(AccountId, Balance)>, account_id: &AccountId, balance: Balance) -> bool {
+    let mut changed = false;
+    if let Some(index) = entries.iter().position(|(id, _)| id == account_id) {
+        entries.remove(index);
+        changed = true;
+    }
+    let qualified = entries.len() < LEADERBOARD_SIZE || entries.last().map(|(_, min)| balance > *min).unwrap_or(true);
+    if balance > 0 && qualified {
+        let index = entries.partition_point(|(_, other)| *other >= balance);
+        entries.insert(index, (account_id.clone(), balance));
+        entries.truncate(LEADERBOARD_SIZE);
+        changed = true;
+    }
+    changed
+}
@@ -24,17 +24,33 @@ use near_non_transferable_token::fungible_token::metadata::{
     FungibleTokenMetadata, FungibleTokenMetadataProvider, FT_METADATA_SPEC,
 };
 
-use near_non_transferable_token::{impl_fungible_token_core, impl_fungible_token_storage};
 use near_non_transferable_token::storage_management::{
     StorageManagement, StorageBalance, StorageBalanceBounds
 };
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::{LazyOption, UnorderedMap, UnorderedSet, LookupMap};
-use near_sdk::json_types::{U128};
+use near_sdk::collections::{LazyOption, UnorderedMap, UnorderedSet, LookupMap, Vector};
+use near_sdk::json_types::{U128, U64};
 use near_sdk::serde::{Serialize, Deserialize};
 use near_sdk::serde_json::{json, self};
 use near_sdk::{env, log, near_bindgen, AccountId, Balance, PanicOnDefault, PromiseOrValue, Promise, Gas, bs58, base64};
-use utils::{get_root_id};
+use utils::{get_root_id, get_parent_id, get_storage_key, get_pair_storage_key, refund_deposit};
+use checkpoint::Checkpoint;
+use polls::Poll;
+use slash::SlashRecord;
+use revocation::Revocation;
+use consume::Approval;
+use lock::Lock;
+use vouch::Vouch;
+use classes::{TokenClass, internal_register_collector};
+use roles::Role;
+use gas::GasConfig;
+use pause::{PauseFlags, QueuedMint};
+use timelock::{AdminAction, PendingAction};
+use council::{Council, Proposal};
+use metadata::SourceMetadata;
+use application::Application;
+use sybil::SybilPolicy;
+use ban::Ban;
 use std::collections::{HashSet, HashMap};
 use std::convert::{TryFrom, TryInto};
 
@@ -43,6 +59,33 @@ pub mod utils;
 pub mod resolver;
 pub mod internal;
 pub mod view;
+pub mod checkpoint;
+pub mod delegate;
+pub mod polls;
+pub mod slash;
+pub mod events;
+pub mod revocation;
+pub mod holders;
+pub mod storage;
+pub mod leaderboard;
+pub mod burn;
+pub mod consume;
+pub mod lock;
+pub mod vouch;
+pub mod gate;
+pub mod classes;
+pub mod subtree;
+pub mod ownership;
+pub mod roles;
+pub mod gas;
+pub mod pause;
+pub mod timelock;
+pub mod council;
+pub mod upgrade;
+pub mod metadata;
+pub mod application;
+pub mod sybil;
+pub mod ban;
 
 
 #[near_bindgen]
@@ -50,8 +93,59 @@ pub mod view;
 pub struct Contract {
     token: FungibleToken,
     metadata: LazyOption<FungibleTokenMetadata>,
-    owner_id: AccountId,
-    white_list: HashSet<AccountId>
+    owner_id: Option<AccountId>,
+    pending_owner_id: Option<AccountId>,
+    white_list: HashSet<AccountId>,
+    account_checkpoints: LookupMap<AccountId, Vector<Checkpoint>>,
+    supply_checkpoints: Vector<Checkpoint>,
+    delegates: LookupMap<AccountId, AccountId>,
+    vote_checkpoints: LookupMap<AccountId, Vector<Checkpoint>>,
+    source_checkpoints: LookupMap<(AccountId, AccountId), Vector<Checkpoint>>,
+    polls: Vector<Poll>,
+    poll_votes: LookupMap<(u64, AccountId), u32>,
+    slashes: Vector<SlashRecord>,
+    account_slashes: LookupMap<AccountId, Vec<u64>>,
+    source_holders: LookupMap<AccountId, UnorderedSet<AccountId>>,
+    revocations: UnorderedMap<AccountId, Revocation>,
+    frozen_balances: LookupMap<(AccountId, AccountId), Balance>,
+    holders: UnorderedSet<AccountId>,
+    leaderboards: LookupMap<Option<AccountId>, Vec<(AccountId, Balance)>>,
+    approvals: LookupMap<(AccountId, AccountId), Approval>,
+    locks: LookupMap<u64, Lock>,
+    next_lock_id: u64,
+    account_locks: LookupMap<AccountId, Vec<u64>>,
+    locked_balances: LookupMap<AccountId, Balance>,
+    purpose_locks: LookupMap<(AccountId, AccountId), Balance>,
+    vouches: LookupMap<u64, Vouch>,
+    next_vouch_id: u64,
+    account_vouches: LookupMap<AccountId, Vec<u64>>,
+    vouched_scores: LookupMap<AccountId, Balance>,
+    lock_vouches: LookupMap<u64, u64>,
+    vouch_slash_bps: u32,
+    tier_thresholds: Vec<Balance>,
+    classes: UnorderedMap<String, TokenClass>,
+    supply_cap: Option<Balance>,
+    subtree_balances: LookupMap<(AccountId, AccountId), Balance>,
+    subtree_supplies: LookupMap<AccountId, Balance>,
+    roles: LookupMap<Role, UnorderedSet<AccountId>>,
+    gas_config: GasConfig,
+    pause_flags: PauseFlags,
+    queued_mints: Vector<QueuedMint>,
+    pending_actions: UnorderedMap<u64, PendingAction>,
+    next_action_id: u64,
+    timelock_delay: u64,
+    council: Option<Council>,
+    proposals: Vector<Proposal>,
+    staged_code: LazyOption<Vec<u8>>,
+    state_version: u32,
+    source_metadata: LookupMap<AccountId, SourceMetadata>,
+    applications: UnorderedMap<AccountId, Application>,
+    application_bond: Balance,
+    personhood_registry: Option<AccountId>,
+    sybil_policies: LookupMap<AccountId, SybilPolicy>,
+    bans: LookupMap<AccountId, Ban>,
+    /// sources with a queued revocation, mapped to its action id
+    suspended_sources: LookupMap<AccountId, u64>
 }
 
 #[derive(BorshDeserialize, BorshSerialize)]
@@ -74,8 +168,15 @@ const DATA_IMAGE_SVG_NEAR_ICON: &str = "data:image/png;base64,iVBORw0KGgoAAAANSU
 
 const THIS_FUNCTION_CALL_GAS: u64  = 50_000_000_000_000;
 const COLLECT_DRIP_GAS: u64 = 10_000_000_000_000;
-const RESOLVE_COLLECT_DRIP_GAS_BASE: u64 = 3_000_000_000_000;
-const RESOLVE_COLLECT_DRIP_GAS_X: u64 = 2_000_000_000_000;
+/// covers the registry answer, the queued mint bookkeeping and the storage refund
+const RESOLVE_COLLECT_DRIP_GAS_BASE: u64 = 5_000_000_000_000;
+/// per mint: three checkpoint vectors, the delegatee's votes, holder sets, a subtree entry per ancestor
+/// and rewriting two leaderboards of up to 100 entries, the leaderboards being the bulk of it
+const RESOLVE_COLLECT_DRIP_GAS_X: u64 = 12_000_000_000_000;
+const FT_ON_BURN_GAS: u64 = 10_000_000_000_000;
+const FT_RESOLVE_BURN_GAS: u64 = 10_000_000_000_000;
+/// storage a single collect may add to checkpoints, leaderboards and holder sets
+const COLLECT_STORAGE_BYTES: u64 = 1_000;
 
 #[near_bindgen]
 impl Contract {
@@ -102,64 +203,84 @@ impl Contract {
     ) -> Self {
         assert!(!env::state_exists(), "Already initialized");
         metadata.assert_valid();
-        let mut this = Self {
-            token: FungibleToken::new(b"a".to_vec()),
-            metadata: LazyOption::new(b"m".to_vec(), Some(&metadata)),
-            owner_id,
-            white_list: HashSet::new()
-        };
+        let mut this = Self::internal_new(
+            FungibleToken::new(b"a".to_vec()),
+            LazyOption::new(b"m".to_vec(), Some(&metadata)),
+            Some(owner_id),
+            HashSet::new()
+        );
         this
     }
 
+    /// Removals are queued behind the timelock.
     pub fn set_white_list(&mut self, contract_id: AccountId, del: bool) {
-        assert!(env::predecessor_account_id() == self.owner_id, "not owner");
-        match del {
-            true => self.white_list.remove(&contract_id),
-            false => self.white_list.insert(contract_id)
-        };
+        self.assert_role(Role::WhitelistManager);
+        if del {
+            self.internal_queue_action(AdminAction::RemoveFromWhiteList { contract_id });
+            return
+        }
+        self.white_list.insert(contract_id);
     }
 
+    /// The deposit left after registration, together with the storage balance, must cover
+    /// `COLLECT_STORAGE_BYTES` per collect. Whatever the mints don't use is refunded in `resolve_collect`.
     #[payable]
-    pub fn ft_collect(&mut self, collects: Vec<AccountId>) {
+    pub fn ft_collect(&mut self, collects: Vec<AccountId>, class_id: Option<String>) {
+        let initial_storage_usage = env::storage_usage();
         let sender_id = env::predecessor_account_id();
+        self.assert_not_paused(self.pause_flags.collect);
+        assert!(!self.internal_is_banned(&sender_id), "banned");
 
-        let storage_balance = match self.token.storage_balance_of(sender_id.clone()) {
-            Some(v) => v.available.0,
+        let collects: Vec<AccountId> = collects.into_iter().filter(|contract_id| {
+            self.internal_is_class_source(&class_id, contract_id)
+        }).collect();
+        let storage_balance = match &class_id {
+            Some(class_id) => {
+                let mut class = self.classes.get(class_id).expect("class not found");
+                let storage_balance = internal_register_collector(&mut class.token, &sender_id, &collects);
+                self.classes.insert(class_id, &class);
+                storage_balance
+            },
             None => {
-                assert!(self.token.account_storage_usage as u128 * env::storage_byte_cost() < env::attached_deposit(), "not registered");
-                self.token.internal_register_account(&sender_id);
-                0
+                let storage_balance = internal_register_collector(&mut self.token, &sender_id, &collects);
+                self.holders.insert(&sender_id);
+                storage_balance
             }
         };
+        // like source registration, the storage balance counts before any new attachment
+        let register_cost = env::storage_usage().saturating_sub(initial_storage_usage) as u128 * env::storage_byte_cost();
+        let attached_deposit = env::attached_deposit().saturating_sub(collects.len() as u128);
+        let reserved = collects.len() as u128 * COLLECT_STORAGE_BYTES as u128 * env::storage_byte_cost();
+        assert!(attached_deposit + storage_balance >= register_cost + reserved, "not enough deposit");
+        let storage_deposit = attached_deposit.saturating_sub(register_cost);
 
-        let account = self.token.accounts.get(&sender_id).unwrap();
-        let mut unregister_count = 0;
-        let collects: Vec<AccountId> = collects.into_iter().filter(|contract_id| {
-            if get_root_id(contract_id.clone()) == get_root_id(env::current_account_id()) || self.white_list.get(&contract_id).is_some() {
-                if account.is_registered(&contract_id) == false {
-                    unregister_count += 1;
-                }
-                true
-            } else {
-                false
-            }
-        }).collect();
-        assert!(self.token.account_storage_usage as u128 * env::storage_byte_cost() * unregister_count <= env::attached_deposit() + storage_balance, "not enough deposit");
-
-        assert!(collects.len() as u64 * (COLLECT_DRIP_GAS + RESOLVE_COLLECT_DRIP_GAS_X) + RESOLVE_COLLECT_DRIP_GAS_BASE < (env::prepaid_gas() - Gas::from(THIS_FUNCTION_CALL_GAS)).0, "not enough gas");
+        let gas_config = self.gas_config.clone();
+        let check_human = self.internal_needs_human_check(&collects);
+        let promise_count = collects.len() as u64 + check_human as u64;
+        assert!(promise_count * (gas_config.collect_drip_gas.0 + gas_config.resolve_collect_drip_gas_x.0) + gas_config.resolve_collect_drip_gas_base.0 < (env::prepaid_gas() - Gas::from(gas_config.this_function_call_gas.0)).0, "not enough gas");
 
         let mut promises: Vec<u64> = Vec::new();
         for contract_id in collects.clone() {
             let new_promise = env::promise_create(contract_id.clone(), "collect_drip", json!({
-            }).to_string().as_bytes(), 1, COLLECT_DRIP_GAS.into());
+            }).to_string().as_bytes(), 1, gas_config.collect_drip_gas.0.into());
+            promises.push(new_promise);
+        }
+        // the registry answer comes last so result indexes still match `collects`
+        if check_human {
+            let new_promise = env::promise_create(self.personhood_registry.clone().unwrap(), "is_human", json!({
+                "account_id": sender_id
+            }).to_string().as_bytes(), 0, gas_config.collect_drip_gas.0.into());
             promises.push(new_promise);
         }
 
-        let remain_gas = env::prepaid_gas() - env::used_gas() - Gas::from(collects.len() as u64 * COLLECT_DRIP_GAS + RESOLVE_COLLECT_DRIP_GAS_BASE);
+        let remain_gas = env::prepaid_gas() - env::used_gas() - Gas::from(promise_count * gas_config.collect_drip_gas.0 + gas_config.resolve_collect_drip_gas_base.0);
         let batch_promise = env::promise_and(&promises[..]);
         env::promise_then(batch_promise, env::current_account_id(), "resolve_collect", json!({
             "collects": collects,
-            "account_id": sender_id
+            "account_id": sender_id,
+            "class_id": class_id,
+            "check_human": check_human,
+            "storage_deposit": U128(storage_deposit)
         }).to_string().as_bytes(), 0, remain_gas);
 
         assert!(promises.len() > 0, "failed");
@@ -167,8 +288,186 @@ impl Contract {
 
 }
 
-impl_fungible_token_core!(Contract, token);
-impl_fungible_token_storage!(Contract, token);
+impl Contract {
+    /// Fresh state around an existing token, shared by `new` and the migration from the first layout.
+    pub(crate) fn internal_new(
+        token: FungibleToken,
+        metadata: LazyOption<FungibleTokenMetadata>,
+        owner_id: Option<AccountId>,
+        white_list: HashSet<AccountId>
+    ) -> Self {
+        Self {
+            token,
+            metadata,
+            owner_id,
+            pending_owner_id: None,
+            white_list,
+            account_checkpoints: LookupMap::new(b"c".to_vec()),
+            supply_checkpoints: Vector::new(b"s".to_vec()),
+            delegates: LookupMap::new(b"d".to_vec()),
+            vote_checkpoints: LookupMap::new(b"v".to_vec()),
+            source_checkpoints: LookupMap::new(b"e".to_vec()),
+            polls: Vector::new(b"p".to_vec()),
+            poll_votes: LookupMap::new(b"q".to_vec()),
+            slashes: Vector::new(b"l".to_vec()),
+            account_slashes: LookupMap::new(b"k".to_vec()),
+            source_holders: LookupMap::new(b"h".to_vec()),
+            revocations: UnorderedMap::new(b"r".to_vec()),
+            frozen_balances: LookupMap::new(b"f".to_vec()),
+            holders: UnorderedSet::new(b"g".to_vec()),
+            leaderboards: LookupMap::new(b"b".to_vec()),
+            approvals: LookupMap::new(b"o".to_vec()),
+            locks: LookupMap::new(b"x".to_vec()),
+            next_lock_id: 0,
+            account_locks: LookupMap::new(b"y".to_vec()),
+            locked_balances: LookupMap::new(b"z".to_vec()),
+            purpose_locks: LookupMap::new(b"w".to_vec()),
+            vouches: LookupMap::new(b"u".to_vec()),
+            next_vouch_id: 0,
+            account_vouches: LookupMap::new(b"t".to_vec()),
+            vouched_scores: LookupMap::new(b"n".to_vec()),
+            lock_vouches: LookupMap::new(b"L".to_vec()),
+            vouch_slash_bps: 0,
+            tier_thresholds: vec![],
+            classes: UnorderedMap::new(b"i".to_vec()),
+            supply_cap: None,
+            subtree_balances: LookupMap::new(b"j".to_vec()),
+            subtree_supplies: LookupMap::new(b"J".to_vec()),
+            roles: LookupMap::new(b"R".to_vec()),
+            gas_config: GasConfig::default(),
+            pause_flags: PauseFlags::default(),
+            queued_mints: Vector::new(b"Q".to_vec()),
+            pending_actions: UnorderedMap::new(b"P".to_vec()),
+            next_action_id: 0,
+            timelock_delay: timelock::DEFAULT_TIMELOCK_DELAY,
+            council: None,
+            proposals: Vector::new(b"M".to_vec()),
+            staged_code: LazyOption::new(b"W".to_vec(), None),
+            state_version: upgrade::STATE_VERSION,
+            source_metadata: LookupMap::new(b"N".to_vec()),
+            applications: UnorderedMap::new(b"K".to_vec()),
+            application_bond: application::DEFAULT_APPLICATION_BOND,
+            personhood_registry: None,
+            sybil_policies: LookupMap::new(b"G".to_vec()),
+            bans: LookupMap::new(b"B".to_vec()),
+            suspended_sources: LookupMap::new(b"D".to_vec())
+        }
+    }
+}
+
+/// Written out instead of `impl_fungible_token_core!` so every balance change is recorded
+/// in checkpoints, votes and the other indexes.
+#[near_bindgen]
+impl Contract {
+    pub fn ft_balance_of(&self, account_id: AccountId) -> U128 {
+        self.token.ft_balance_of(account_id)
+    }
+
+    pub fn ft_total_balance_of(&self, account_id: AccountId) -> U128 {
+        self.token.ft_total_balance_of(account_id)
+    }
+
+    pub fn ft_total_supply(&self) -> U128 {
+        self.token.ft_total_supply()
+    }
+
+    pub fn ft_available_supply(&self) -> U128 {
+        self.token.ft_available_supply()
+    }
+
+    /// Locked drip can't be deposited.
+    #[payable]
+    pub fn ft_deposit_call(&mut self, receiver_id: AccountId, amount: U128, msg: String) -> PromiseOrValue<U128> {
+        let sender_id = env::predecessor_account_id();
+        self.internal_assert_available(&sender_id, amount.0);
+        let snapshots = self.internal_snapshot_sources(&sender_id);
+        let result = self.token.ft_deposit_call(receiver_id, amount, msg);
+        self.internal_record_drip_changes(&sender_id, snapshots);
+        result
+    }
+
+    #[payable]
+    pub fn ft_withdraw_call(&mut self, receiver_id: AccountId, amount: U128, msg: String) -> PromiseOrValue<U128> {
+        let sender_id = env::predecessor_account_id();
+        let snapshots = self.internal_snapshot_sources(&sender_id);
+        let result = self.token.ft_withdraw_call(receiver_id, amount, msg);
+        self.internal_record_drip_changes(&sender_id, snapshots);
+        result
+    }
+
+    /// Burns through `internal_burn` right away, whatever `receiver_id` reports unused from `ft_on_burn` is re-minted.
+    #[payable]
+    pub fn ft_burn_call(&mut self, receiver_id: AccountId, amount: U128, msg: String) -> PromiseOrValue<U128> {
+        near_sdk::assert_one_yocto();
+        self.assert_not_paused(self.pause_flags.burn);
+        let sender_id = env::predecessor_account_id();
+        self.internal_assert_available(&sender_id, amount.0);
+        let contract_ids = self.internal_get_drip_sources(&sender_id);
+        let burned: Vec<(AccountId, U128)> = self.internal_burn_from_sources(&sender_id, contract_ids, amount.0, json!({
+            "receiver_id": receiver_id
+        })).into_iter().map(|(contract_id, amount)| (contract_id, amount.into())).collect();
+
+        Promise::new(receiver_id.clone())
+            .function_call("ft_on_burn".to_string(), json!({
+                "owner_id": sender_id,
+                "amount": amount,
+                "msg": msg
+            }).to_string().into_bytes(), 0, Gas(FT_ON_BURN_GAS))
+            .then(Promise::new(env::current_account_id()).function_call("ft_resolve_burn".to_string(), json!({
+                "owner_id": sender_id,
+                "receiver_id": receiver_id,
+                "amount": amount,
+                "burned": burned
+            }).to_string().into_bytes(), 0, Gas(FT_RESOLVE_BURN_GAS)))
+            .into()
+    }
+
+    #[private]
+    pub fn ft_resolve_deposit(&mut self, owner_id: AccountId, receiver_id: AccountId, amount: U128) -> U128 {
+        let snapshots = self.internal_snapshot_sources(&owner_id);
+        let result = self.token.ft_resolve_deposit(owner_id.clone(), receiver_id, amount);
+        self.internal_record_drip_changes(&owner_id, snapshots);
+        result
+    }
+
+    #[private]
+    pub fn ft_resolve_withdraw(&mut self, owner_id: AccountId, receiver_id: AccountId, amount: U128) -> U128 {
+        let snapshots = self.internal_snapshot_sources(&owner_id);
+        let result = self.token.ft_resolve_withdraw(owner_id.clone(), receiver_id, amount);
+        self.internal_record_drip_changes(&owner_id, snapshots);
+        result
+    }
+
+    /// Returns the amount that stayed burned. A failed `ft_on_burn` re-mints everything.
+    #[private]
+    pub fn ft_resolve_burn(&mut self, owner_id: AccountId, receiver_id: AccountId, amount: U128, burned: Vec<(AccountId, U128)>) -> U128 {
+        let unused = match env::promise_result(0) {
+            near_sdk::PromiseResult::Successful(result) => serde_json::from_slice::<U128>(&result)
+                .map(|unused| std::cmp::min(unused.0, amount.0))
+                .unwrap_or(0),
+            _ => amount.0
+        };
+        let mut refund = unused;
+        if self.token.accounts.get(&owner_id).is_some() {
+            for (contract_id, burned_amount) in burned {
+                if refund == 0 {
+                    break
+                }
+                if !self.internal_is_source(&contract_id) {
+                    continue
+                }
+                let refund_amount = std::cmp::min(refund, burned_amount.0);
+                self.internal_mint(&owner_id, refund_amount, &contract_id, json!({
+                    "contract_id": contract_id,
+                    "receiver_id": receiver_id,
+                    "refund": true
+                }).to_string());
+                refund -= refund_amount;
+            }
+        }
+        (amount.0 - (unused - refund)).into()
+    }
+}
 
 
 #[near_bindgen]
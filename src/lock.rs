@@ -0,0 +1,168 @@
+use crate::*;
+use crate::events::emit_event;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Lock {
+    pub account_id: AccountId,
+    pub purpose_id: AccountId,
+    pub amount: U128,
+    pub unlock_at: U64
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Locked drip still counts in `ft_balance_of` but can't be burned or spent by its holder.
+    /// The attached deposit pays for the lock's storage, the rest is refunded.
+    #[payable]
+    pub fn drip_lock(&mut self, purpose_contract: AccountId, amount: U128, unlock_at: U64) -> u64 {
+        assert!(env::attached_deposit() > 0, "requires attached deposit");
+        let initial_storage_usage = env::storage_usage();
+        let sender_id = env::predecessor_account_id();
+        assert!(amount.0 > 0, "invalid amount");
+        assert!(purpose_contract != env::current_account_id(), "use vouch");
+        assert!(unlock_at.0 > env::block_timestamp(), "unlock time passed");
+        self.internal_assert_available(&sender_id, amount.0);
+        let lock_id = self.internal_create_lock(sender_id, purpose_contract, amount.0, unlock_at);
+        refund_deposit(initial_storage_usage);
+        lock_id
+    }
+
+    pub fn drip_unlock(&mut self, lock_id: u64) {
+        let lock = self.locks.get(&lock_id).expect("lock not found");
+        assert!(env::predecessor_account_id() == lock.account_id, "not lock owner");
+        assert!(lock.purpose_id != env::current_account_id(), "use release_vouch");
+        assert!(env::block_timestamp() >= lock.unlock_at.0, "still locked");
+        self.internal_remove_lock(lock_id);
+    }
+
+    /// Called by the purpose contract to burn part of a lock, returns the amount actually slashed.
+    pub fn drip_slash_lock(&mut self, lock_id: u64, amount: U128, reason: String) -> U128 {
+        self.assert_not_paused(self.pause_flags.burn);
+        let lock = self.locks.get(&lock_id).expect("lock not found");
+        assert!(env::predecessor_account_id() == lock.purpose_id, "not purpose contract");
+        let amount = std::cmp::min(amount.0, std::cmp::min(lock.amount.0, self.ft_balance_of(lock.account_id.clone()).0));
+        self.internal_reduce_lock(lock_id, amount);
+        let contract_ids = self.internal_get_drip_sources(&lock.account_id);
+        self.internal_burn_from_sources(&lock.account_id, contract_ids, amount, json!({
+            "lock_id": lock_id,
+            "reason": reason
+        }));
+        emit_event("lock_slash", json!({
+            "lock_id": lock_id,
+            "account_id": lock.account_id,
+            "purpose_id": lock.purpose_id,
+            "amount": U128(amount),
+            "reason": reason
+        }));
+        amount.into()
+    }
+
+    pub fn get_locked(&self, account_id: AccountId, purpose_contract: AccountId) -> U128 {
+        self.purpose_locks.get(&(account_id, purpose_contract)).unwrap_or(0).into()
+    }
+
+    pub fn get_locks(&self, account_id: AccountId) -> Vec<(u64, Lock)> {
+        self.account_locks.get(&account_id).unwrap_or_default().into_iter()
+            .map(|lock_id| (lock_id, self.locks.get(&lock_id).unwrap()))
+            .collect()
+    }
+
+    pub fn get_available_balance(&self, account_id: AccountId) -> U128 {
+        self.internal_get_available(&account_id).into()
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_get_available(&self, account_id: &AccountId) -> Balance {
+        let locked = self.locked_balances.get(account_id).unwrap_or(0);
+        self.ft_balance_of(account_id.clone()).0.saturating_sub(locked)
+    }
+
+    pub(crate) fn internal_assert_available(&self, account_id: &AccountId, amount: Balance) {
+        self.assert_not_frozen(account_id);
+        assert!(self.internal_get_available(account_id) >= amount, "balance locked");
+    }
+
+    pub(crate) fn internal_create_lock(&mut self, account_id: AccountId, purpose_id: AccountId, amount: Balance, unlock_at: U64) -> u64 {
+        let lock_id = self.next_lock_id;
+        self.next_lock_id += 1;
+        let lock = Lock {
+            account_id: account_id.clone(),
+            purpose_id: purpose_id.clone(),
+            amount: amount.into(),
+            unlock_at
+        };
+        self.locks.insert(&lock_id, &lock);
+        let mut lock_ids = self.account_locks.get(&account_id).unwrap_or_default();
+        lock_ids.push(lock_id);
+        self.account_locks.insert(&account_id, &lock_ids);
+        self.internal_update_locked(&account_id, &purpose_id, amount, true);
+        emit_event("drip_lock", json!({
+            "lock_id": lock_id,
+            "lock": lock
+        }));
+        lock_id
+    }
+
+    pub(crate) fn internal_remove_lock(&mut self, lock_id: u64) {
+        let lock = self.locks.remove(&lock_id).expect("lock not found");
+        let mut lock_ids = self.account_locks.get(&lock.account_id).unwrap_or_default();
+        lock_ids.retain(|id| *id != lock_id);
+        self.account_locks.insert(&lock.account_id, &lock_ids);
+        self.internal_update_locked(&lock.account_id, &lock.purpose_id, lock.amount.0, false);
+        emit_event("drip_unlock", json!({
+            "lock_id": lock_id,
+            "account_id": lock.account_id,
+            "amount": lock.amount
+        }));
+    }
+
+    pub(crate) fn internal_reduce_lock(&mut self, lock_id: u64, amount: Balance) {
+        let mut lock = self.locks.get(&lock_id).expect("lock not found");
+        lock.amount = (lock.amount.0 - amount).into();
+        self.locks.insert(&lock_id, &lock);
+        self.internal_update_locked(&lock.account_id, &lock.purpose_id, amount, false);
+    }
+
+    /// Shrinks the newest locks first when a burn leaves less drip than is locked.
+    pub(crate) fn internal_clamp_locks(&mut self, account_id: &AccountId) {
+        let locked = self.locked_balances.get(account_id).unwrap_or(0);
+        let mut excess = locked.saturating_sub(self.ft_balance_of(account_id.clone()).0);
+        for lock_id in self.account_locks.get(account_id).unwrap_or_default().into_iter().rev() {
+            if excess == 0 {
+                break
+            }
+            let amount = std::cmp::min(excess, self.locks.get(&lock_id).unwrap().amount.0);
+            match self.lock_vouches.get(&lock_id) {
+                Some(vouch_id) => self.internal_reduce_vouch(vouch_id, amount),
+                None => self.internal_reduce_lock(lock_id, amount)
+            }
+            excess -= amount;
+            emit_event("lock_clamp", json!({
+                "lock_id": lock_id,
+                "account_id": account_id,
+                "amount": U128(amount)
+            }));
+        }
+    }
+
+    pub(crate) fn internal_extend_lock(&mut self, lock_id: u64, amount: Balance) {
+        let mut lock = self.locks.get(&lock_id).expect("lock not found");
+        lock.amount = (lock.amount.0 + amount).into();
+        self.locks.insert(&lock_id, &lock);
+        self.internal_update_locked(&lock.account_id, &lock.purpose_id, amount, true);
+    }
+
+    fn internal_update_locked(&mut self, account_id: &AccountId, purpose_id: &AccountId, amount: Balance, increase: bool) {
+        let update = |locked: Balance| match increase {
+            true => locked + amount,
+            false => locked - amount
+        };
+        let locked = update(self.locked_balances.get(account_id).unwrap_or(0));
+        self.locked_balances.insert(account_id, &locked);
+        let key = (account_id.clone(), purpose_id.clone());
+        let locked = update(self.purpose_locks.get(&key).unwrap_or(0));
+        self.purpose_locks.insert(&key, &locked);
+    }
+}
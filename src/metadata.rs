@@ -0,0 +1,75 @@
+use crate::*;
+use crate::events::emit_event;
+use crate::roles::Role;
+use near_sdk::json_types::Base64VecU8;
+
+const MAX_ICON_LENGTH: usize = 16 * 1024;
+const MAX_SOURCE_NAME_LENGTH: usize = 64;
+
+/// How a source is shown next to the drip it issued.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SourceMetadata {
+    pub name: String,
+    pub icon: Option<String>
+}
+
+impl SourceMetadata {
+    pub(crate) fn assert_valid(&self) {
+        assert!(!self.name.is_empty() && self.name.len() <= MAX_SOURCE_NAME_LENGTH, "invalid name");
+        assert_valid_icon(&self.icon);
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn update_ft_metadata(&mut self, name: String, icon: Option<String>, reference: Option<String>, reference_hash: Option<Base64VecU8>) {
+        self.assert_role(Role::MetadataEditor);
+        assert_valid_icon(&icon);
+        let mut metadata = self.metadata.get().unwrap();
+        metadata.name = name;
+        metadata.icon = icon;
+        metadata.reference = reference;
+        metadata.reference_hash = reference_hash;
+        metadata.assert_valid();
+        self.metadata.set(&metadata);
+        emit_event("metadata_update", json!({
+            "name": metadata.name,
+            "reference": metadata.reference,
+            "sender_id": env::predecessor_account_id()
+        }));
+    }
+
+    /// Callable by a metadata editor or the source itself, None clears it. The caller pays for the storage.
+    #[payable]
+    pub fn set_source_metadata(&mut self, contract_id: AccountId, metadata: Option<SourceMetadata>) {
+        let initial_storage_usage = env::storage_usage();
+        let sender_id = env::predecessor_account_id();
+        assert!(self.internal_is_owner_or_role(Role::MetadataEditor, &sender_id) || sender_id == contract_id, "not metadata editor or source");
+        match &metadata {
+            Some(metadata) => {
+                assert!(self.internal_is_source(&contract_id), "not source");
+                metadata.assert_valid();
+                self.source_metadata.insert(&contract_id, metadata);
+            },
+            None => {
+                self.source_metadata.remove(&contract_id);
+            }
+        }
+        emit_event("source_metadata_update", json!({
+            "contract_id": contract_id,
+            "metadata": metadata
+        }));
+        refund_deposit(initial_storage_usage);
+    }
+
+    pub fn get_source_metadata(&self, contract_id: AccountId) -> Option<SourceMetadata> {
+        self.source_metadata.get(&contract_id)
+    }
+}
+
+fn assert_valid_icon(icon: &Option<String>) {
+    if let Some(icon) = icon {
+        assert!(icon.len() <= MAX_ICON_LENGTH, "icon too large");
+    }
+}
@@ -0,0 +1,72 @@
+use near_sdk::assert_one_yocto;
+
+use crate::*;
+use crate::events::emit_event;
+
+#[near_bindgen]
+impl Contract {
+    /// First step of a two-step transfer, `new_owner` has to call `accept_ownership`.
+    #[payable]
+    pub fn propose_owner(&mut self, new_owner: AccountId) {
+        assert_one_yocto();
+        self.assert_owner();
+        self.pending_owner_id = Some(new_owner.clone());
+        emit_event("owner_propose", json!({
+            "owner_id": self.owner_id,
+            "pending_owner_id": new_owner
+        }));
+    }
+
+    pub fn accept_ownership(&mut self) {
+        let sender_id = env::predecessor_account_id();
+        assert!(self.pending_owner_id.as_ref() == Some(&sender_id), "not pending owner");
+        let old_owner_id = self.owner_id.replace(sender_id.clone());
+        self.pending_owner_id = None;
+        emit_event("owner_transfer", json!({
+            "old_owner_id": old_owner_id,
+            "new_owner_id": sender_id
+        }));
+    }
+
+    pub fn cancel_owner_proposal(&mut self) {
+        self.assert_owner();
+        let pending_owner_id = self.pending_owner_id.take().expect("no pending owner");
+        emit_event("owner_proposal_cancel", json!({
+            "pending_owner_id": pending_owner_id
+        }));
+    }
+
+    /// Leaves the contract without an owner, every owner-only method becomes unreachable.
+    #[payable]
+    pub fn renounce_ownership(&mut self) {
+        assert_one_yocto();
+        self.assert_owner();
+        let old_owner_id = self.owner_id.take();
+        self.pending_owner_id = None;
+        emit_event("owner_renounce", json!({
+            "old_owner_id": old_owner_id
+        }));
+    }
+
+    pub fn get_owner(&self) -> Option<AccountId> {
+        self.owner_id.clone()
+    }
+
+    pub fn get_pending_owner(&self) -> Option<AccountId> {
+        self.pending_owner_id.clone()
+    }
+}
+
+impl Contract {
+    /// While a council is set only its own calls, made through proposals, count as the owner.
+    pub(crate) fn internal_is_owner(&self, account_id: &AccountId) -> bool {
+        match self.council {
+            Some(_) => account_id == &env::current_account_id(),
+            None => self.owner_id.as_ref() == Some(account_id)
+        }
+    }
+
+    pub(crate) fn assert_owner(&self) {
+        assert!(self.internal_is_owner(&env::predecessor_account_id()), "not owner");
+    }
+}
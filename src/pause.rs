@@ -0,0 +1,91 @@
+use crate::*;
+use crate::events::emit_event;
+use crate::roles::Role;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PauseFlags {
+    pub collect: bool,
+    /// mints from `resolve_collect` are queued instead of credited
+    pub mint: bool,
+    pub storage: bool,
+    pub burn: bool
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct QueuedMint {
+    pub account_id: AccountId,
+    pub contract_id: AccountId,
+    pub amount: U128,
+    pub class_id: Option<String>
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn set_pause_flags(&mut self, pause_flags: PauseFlags) {
+        self.assert_role(Role::Pauser);
+        self.internal_set_pause_flags(pause_flags);
+    }
+
+    pub fn pause_all(&mut self) {
+        self.set_pause_flags(PauseFlags {
+            collect: true,
+            mint: true,
+            storage: true,
+            burn: true
+        });
+    }
+
+    pub fn get_pause_flags(&self) -> PauseFlags {
+        self.pause_flags.clone()
+    }
+
+    /// Credits up to `limit` mints queued while minting was paused, returns how many remain.
+    pub fn process_queued_mints(&mut self, limit: u64) -> u64 {
+        assert!(!self.pause_flags.mint, "mint paused");
+        for _ in 0..limit {
+            match self.queued_mints.pop() {
+                Some(queued_mint) => match &queued_mint.class_id {
+                    Some(class_id) => self.internal_set_class_drip(queued_mint.amount.0, queued_mint.contract_id, queued_mint.account_id, class_id),
+                    None => self.internal_set_drip(queued_mint.amount.0, queued_mint.contract_id, queued_mint.account_id)
+                },
+                None => break
+            }
+        }
+        self.queued_mints.len()
+    }
+
+    pub fn get_queued_mints(&self, from_index: u64, limit: u64) -> Vec<QueuedMint> {
+        (from_index..std::cmp::min(from_index + limit, self.queued_mints.len()))
+            .map(|index| self.queued_mints.get(index).unwrap())
+            .collect()
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_set_pause_flags(&mut self, pause_flags: PauseFlags) {
+        self.pause_flags = pause_flags.clone();
+        emit_event("pause_update", json!({
+            "pause_flags": pause_flags,
+            "sender_id": env::predecessor_account_id()
+        }));
+    }
+
+    pub(crate) fn internal_queue_mint(&mut self, amount: Balance, contract_id: AccountId, account_id: AccountId, class_id: Option<String>) {
+        let queued_mint = QueuedMint {
+            account_id,
+            contract_id,
+            amount: amount.into(),
+            class_id
+        };
+        self.queued_mints.push(&queued_mint);
+        emit_event("mint_queue", json!({
+            "queued_mint": queued_mint
+        }));
+    }
+
+    pub(crate) fn assert_not_paused(&self, paused: bool) {
+        assert!(!paused, "paused");
+    }
+}
@@ -0,0 +1,148 @@
+use crate::*;
+use crate::checkpoint::get_checkpoint_balance;
+use crate::utils::integer_sqrt;
+
+const MAX_POLL_OPTIONS: usize = 16;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum TallyMode {
+    Linear,
+    Quadratic
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Poll {
+    pub creator_id: AccountId,
+    pub title: String,
+    pub options: Vec<String>,
+    pub deadline: U64,
+    pub snapshot: U64,
+    pub source_id: Option<AccountId>,
+    pub mode: TallyMode,
+    pub tallies: Vec<U128>,
+    pub voter_count: u64
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PollResults {
+    pub tallies: Vec<U128>,
+    pub voter_count: u64,
+    pub closed: bool,
+    pub winning_option: Option<u32>
+}
+
+#[near_bindgen]
+impl Contract {
+    #[payable]
+    pub fn create_poll(
+        &mut self,
+        title: String,
+        options: Vec<String>,
+        deadline: U64,
+        snapshot: Option<U64>,
+        source_id: Option<AccountId>,
+        mode: TallyMode
+    ) -> u64 {
+        let initial_storage_usage = env::storage_usage();
+        let sender_id = env::predecessor_account_id();
+        assert!(self.internal_is_owner(&sender_id) || self.internal_is_source(&sender_id), "not owner or source");
+        assert!(options.len() >= 2 && options.len() <= MAX_POLL_OPTIONS, "invalid options");
+        let snapshot = snapshot.unwrap_or(env::block_timestamp().into());
+        assert!(snapshot.0 <= env::block_timestamp(), "snapshot in the future");
+        assert!(deadline.0 > env::block_timestamp(), "deadline passed");
+
+        let poll = Poll {
+            creator_id: sender_id,
+            title,
+            tallies: vec![U128(0); options.len()],
+            options,
+            deadline,
+            snapshot,
+            source_id,
+            mode,
+            voter_count: 0
+        };
+        self.polls.push(&poll);
+        refund_deposit(initial_storage_usage);
+        self.polls.len() - 1
+    }
+
+    /// The attached deposit pays for the recorded vote, the rest is refunded.
+    #[payable]
+    pub fn vote(&mut self, poll_id: u64, option: u32) {
+        let initial_storage_usage = env::storage_usage();
+        let sender_id = env::predecessor_account_id();
+        assert!(!self.internal_is_banned(&sender_id), "banned");
+        let mut poll = self.polls.get(poll_id).expect("poll not found");
+        assert!(env::block_timestamp() < poll.deadline.0, "poll closed");
+        assert!((option as usize) < poll.options.len(), "invalid option");
+        let key = (poll_id, sender_id.clone());
+        assert!(self.poll_votes.get(&key).is_none(), "already voted");
+
+        let balance = self.internal_get_poll_balance(&poll, &sender_id);
+        let weight = match poll.mode {
+            TallyMode::Linear => balance,
+            TallyMode::Quadratic => integer_sqrt(balance)
+        };
+        assert!(weight > 0, "no voting weight");
+
+        let tally = poll.tallies[option as usize].0;
+        poll.tallies[option as usize] = (tally + weight).into();
+        poll.voter_count += 1;
+        self.polls.replace(poll_id, &poll);
+        self.poll_votes.insert(&key, &option);
+        refund_deposit(initial_storage_usage);
+    }
+
+    pub fn get_poll(&self, poll_id: u64) -> Option<Poll> {
+        self.polls.get(poll_id)
+    }
+
+    pub fn get_polls(&self, from_index: u64, limit: u64) -> Vec<(u64, Poll)> {
+        (from_index..std::cmp::min(from_index + limit, self.polls.len()))
+            .map(|poll_id| (poll_id, self.polls.get(poll_id).unwrap()))
+            .collect()
+    }
+
+    pub fn get_poll_results(&self, poll_id: u64) -> PollResults {
+        let poll = self.polls.get(poll_id).expect("poll not found");
+        let mut winning_option = None;
+        let mut max = 0;
+        for (i, tally) in poll.tallies.iter().enumerate() {
+            if tally.0 > max {
+                max = tally.0;
+                winning_option = Some(i as u32);
+            } else if tally.0 == max {
+                winning_option = None;
+            }
+        }
+        PollResults {
+            closed: env::block_timestamp() >= poll.deadline.0,
+            tallies: poll.tallies,
+            voter_count: poll.voter_count,
+            winning_option
+        }
+    }
+
+    pub fn get_poll_vote(&self, poll_id: u64, account_id: AccountId) -> Option<u32> {
+        self.poll_votes.get(&(poll_id, account_id))
+    }
+}
+
+impl Contract {
+    fn internal_get_poll_balance(&self, poll: &Poll, account_id: &AccountId) -> Balance {
+        match &poll.source_id {
+            Some(source_id) => {
+                let key = (account_id.clone(), source_id.clone());
+                let balance = self.source_checkpoints.get(&key).and_then(|checkpoints| {
+                    get_checkpoint_balance(&checkpoints, poll.snapshot.0)
+                });
+                balance.unwrap_or_else(|| self.internal_get_drip(account_id, source_id))
+            },
+            None => self.ft_balance_of_at(account_id.clone(), poll.snapshot).0
+        }
+    }
+}
@@ -3,20 +3,58 @@ use crate::*;
 
 #[near_bindgen]
 impl Contract {
+    /// With `check_human` the last promise result is the personhood registry's `is_human` answer,
+    /// either a bool or the list of the account's proof of personhood tokens.
+    /// The part of `storage_deposit` the mints didn't use goes back to the collector,
+    /// minus `COLLECT_STORAGE_BYTES` for every queued mint.
     #[private]
-    pub fn resolve_collect(&mut self, collects: Vec<AccountId>, account_id: AccountId) {
-        let result_count = env::promise_results_count();
+    pub fn resolve_collect(&mut self, collects: Vec<AccountId>, account_id: AccountId, class_id: Option<String>, check_human: bool, storage_deposit: U128) {
+        let initial_storage_usage = env::storage_usage();
+        let mut queued: u128 = 0;
+        let mut result_count = env::promise_results_count();
+        let is_human = match check_human {
+            true => {
+                result_count -= 1;
+                match env::promise_result(result_count) {
+                    near_sdk::PromiseResult::Successful(result) => match serde_json::from_slice(&result) {
+                        Ok(serde_json::Value::Bool(is_human)) => is_human,
+                        Ok(serde_json::Value::Array(tokens)) => !tokens.is_empty(),
+                        _ => false
+                    },
+                    _ => false
+                }
+            },
+            false => true
+        };
         for i in 0..result_count {
             match env::promise_result(i) {
                 near_sdk::PromiseResult::Successful(result) => {
                     let result: U128 = serde_json::from_slice(&result).unwrap_or(0.into());
                     let contract_id = collects.get(i as usize);
-                    if contract_id.is_some() {
-                        self.internal_set_drip(result.0, contract_id.unwrap().clone(), account_id.clone(), );
+                    if contract_id.is_none() {
+                        continue
+                    }
+                    let contract_id = contract_id.unwrap().clone();
+                    let amount = self.internal_apply_sybil_policy(result.0, &contract_id, is_human);
+                    if self.pause_flags.mint {
+                        queued += 1;
+                        self.internal_queue_mint(amount, contract_id, account_id.clone(), class_id.clone());
+                    } else {
+                        match &class_id {
+                            Some(class_id) => self.internal_set_class_drip(amount, contract_id, account_id.clone(), class_id),
+                            None => self.internal_set_drip(amount, contract_id, account_id.clone(), )
+                        }
                     }
                 },
                 _ => continue
             }
         }
+
+        let storage_cost = env::storage_usage().saturating_sub(initial_storage_usage) as u128 * env::storage_byte_cost();
+        let reserved = queued * COLLECT_STORAGE_BYTES as u128 * env::storage_byte_cost();
+        let refund = storage_deposit.0.saturating_sub(storage_cost + reserved);
+        if refund > 0 {
+            Promise::new(account_id).transfer(refund);
+        }
     }
-}
\ No newline at end of file
+}
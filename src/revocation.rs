@@ -0,0 +1,135 @@
+use crate::*;
+use crate::events::emit_event;
+use crate::roles::Role;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum RevocationMode {
+    /// balances leave circulation but are kept aside and can be restored by the owner
+    Freeze,
+    Burn
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Revocation {
+    pub mode: RevocationMode,
+    pub revoked_at: U64,
+    pub processed_accounts: u64,
+    pub processed_amount: U128,
+    pub remaining_accounts: u64,
+    pub completed: bool
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Queues the revocation behind the timelock, returns the action id. The source stops minting right away,
+    /// only the rollback and the bond settlement wait for the timelock.
+    /// `slash_bond` keeps the whitelist application bond of an abusive source instead of refunding it.
+    pub fn revoke_source(&mut self, contract_id: AccountId, mode: RevocationMode, slash_bond: Option<bool>) -> u64 {
+        assert!(self.revocations.get(&contract_id).is_none(), "already revoked");
+        self.internal_queue_action(AdminAction::RevokeSource { contract_id, mode, slash_bond: slash_bond.unwrap_or(false) })
+    }
+
+    /// Rolls back the balances of up to `limit` holders of a revoked source, returns whether it is done.
+    /// Once done it can't run again, so balances restored with `unfreeze_drip` stay.
+    pub fn process_revocation(&mut self, contract_id: AccountId, limit: u64) -> bool {
+        self.assert_not_paused(self.pause_flags.burn);
+        let mut revocation = self.revocations.get(&contract_id).expect("not revoked");
+        assert!(!revocation.completed, "revocation completed");
+        let holders: Vec<AccountId> = match self.source_holders.get(&contract_id) {
+            Some(holders) => holders.iter().take(limit as usize).collect(),
+            None => vec![]
+        };
+        let memo = json!({
+            "contract_id": contract_id,
+            "revocation": revocation.mode
+        }).to_string();
+        for account_id in holders {
+            let balance = self.internal_get_drip(&account_id, &contract_id);
+            if revocation.mode == RevocationMode::Freeze {
+                let key = (account_id.clone(), contract_id.clone());
+                let frozen = self.frozen_balances.get(&key).unwrap_or(0);
+                self.frozen_balances.insert(&key, &(frozen + balance));
+            }
+            self.internal_burn(&account_id, balance, &contract_id, Some(memo.clone()));
+            revocation.processed_accounts += 1;
+            revocation.processed_amount = (revocation.processed_amount.0 + balance).into();
+        }
+        revocation.remaining_accounts = self.internal_source_holder_count(&contract_id);
+        revocation.completed = revocation.remaining_accounts == 0;
+        self.revocations.insert(&contract_id, &revocation);
+        emit_event("revocation_progress", json!({
+            "contract_id": contract_id,
+            "processed_accounts": revocation.processed_accounts,
+            "processed_amount": revocation.processed_amount,
+            "remaining_accounts": revocation.remaining_accounts
+        }));
+        revocation.completed
+    }
+
+    pub fn unfreeze_drip(&mut self, account_id: AccountId, contract_id: AccountId) {
+        self.assert_role(Role::WhitelistManager);
+        self.assert_not_paused(self.pause_flags.mint);
+        assert!(self.revocations.get(&contract_id).map(|revocation| revocation.completed).unwrap_or(true), "revocation in progress");
+        let balance = self.frozen_balances.remove(&(account_id.clone(), contract_id.clone())).expect("nothing frozen");
+        self.internal_mint(&account_id, balance, &contract_id, json!({
+            "contract_id": contract_id,
+            "unfreeze": true
+        }).to_string());
+    }
+
+    pub fn get_revocation(&self, contract_id: AccountId) -> Option<Revocation> {
+        self.revocations.get(&contract_id)
+    }
+
+    /// The id of the queued revocation that suspends the source, if any.
+    pub fn get_source_suspension(&self, contract_id: AccountId) -> Option<u64> {
+        self.suspended_sources.get(&contract_id)
+    }
+
+    pub fn get_frozen_balance(&self, account_id: AccountId, contract_id: AccountId) -> U128 {
+        self.frozen_balances.get(&(account_id, contract_id)).unwrap_or(0).into()
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_revoke_source(&mut self, contract_id: AccountId, mode: RevocationMode, slash_bond: bool) {
+        assert!(self.revocations.get(&contract_id).is_none(), "already revoked");
+        self.suspended_sources.remove(&contract_id);
+        self.white_list.remove(&contract_id);
+        self.internal_settle_bond(&contract_id, slash_bond);
+        let revocation = Revocation {
+            mode,
+            revoked_at: env::block_timestamp().into(),
+            processed_accounts: 0,
+            processed_amount: 0.into(),
+            remaining_accounts: self.internal_source_holder_count(&contract_id),
+            completed: false
+        };
+        self.revocations.insert(&contract_id, &revocation);
+        emit_event("source_revoke", json!({
+            "contract_id": contract_id,
+            "mode": revocation.mode
+        }));
+    }
+
+    pub(crate) fn internal_suspend_source(&mut self, contract_id: &AccountId, action_id: u64) {
+        self.suspended_sources.insert(contract_id, &action_id);
+        emit_event("source_suspend", json!({
+            "contract_id": contract_id,
+            "action_id": action_id
+        }));
+    }
+
+    /// Only the revocation that caused the suspension can lift it.
+    pub(crate) fn internal_lift_suspension(&mut self, contract_id: &AccountId, action_id: u64) {
+        if self.suspended_sources.get(contract_id) == Some(action_id) {
+            self.suspended_sources.remove(contract_id);
+            emit_event("source_resume", json!({
+                "contract_id": contract_id,
+                "action_id": action_id
+            }));
+        }
+    }
+}
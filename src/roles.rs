@@ -0,0 +1,107 @@
+use crate::*;
+use crate::events::emit_event;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum Role {
+    RoleAdmin,
+    WhitelistManager,
+    Pauser,
+    Slasher,
+    Arbiter,
+    MetadataEditor,
+    GasTuner,
+    Moderator
+}
+
+const ROLES: [Role; 8] = [
+    Role::RoleAdmin,
+    Role::WhitelistManager,
+    Role::Pauser,
+    Role::Slasher,
+    Role::Arbiter,
+    Role::MetadataEditor,
+    Role::GasTuner,
+    Role::Moderator
+];
+
+#[near_bindgen]
+impl Contract {
+    /// Only the owner may grant or revoke `RoleAdmin`, role admins manage every other role.
+    pub fn grant_role(&mut self, role: Role, account_id: AccountId) {
+        self.assert_role_manager(role);
+        self.internal_grant_role(role, account_id);
+    }
+
+    pub fn revoke_role(&mut self, role: Role, account_id: AccountId) {
+        self.assert_role_manager(role);
+        self.internal_revoke_role(role, account_id);
+    }
+
+    pub fn has_role(&self, role: Role, account_id: AccountId) -> bool {
+        self.internal_has_role(role, &account_id)
+    }
+
+    pub fn get_role_members(&self, role: Role, from_index: u64, limit: u64) -> Vec<AccountId> {
+        match self.roles.get(&role) {
+            Some(members) => members.iter().skip(from_index as usize).take(limit as usize).collect(),
+            None => vec![]
+        }
+    }
+
+    pub fn get_roles(&self, account_id: AccountId) -> Vec<Role> {
+        ROLES.iter().filter(|role| self.internal_has_role(**role, &account_id)).cloned().collect()
+    }
+}
+
+impl Contract {
+    fn internal_get_role_members(&self, role: Role) -> UnorderedSet<AccountId> {
+        self.roles.get(&role).unwrap_or_else(|| {
+            UnorderedSet::new([b"A".to_vec(), role.try_to_vec().unwrap()].concat())
+        })
+    }
+
+    pub(crate) fn internal_has_role(&self, role: Role, account_id: &AccountId) -> bool {
+        self.roles.get(&role).map(|members| members.contains(account_id)).unwrap_or(false)
+    }
+
+    /// The owner implicitly holds every role.
+    pub(crate) fn internal_is_owner_or_role(&self, role: Role, account_id: &AccountId) -> bool {
+        self.internal_is_owner(account_id) || self.internal_has_role(role, account_id)
+    }
+
+    pub(crate) fn assert_role(&self, role: Role) {
+        assert!(self.internal_is_owner_or_role(role, &env::predecessor_account_id()), "missing role");
+    }
+
+    pub(crate) fn internal_grant_role(&mut self, role: Role, account_id: AccountId) {
+        let mut members = self.internal_get_role_members(role);
+        if members.insert(&account_id) {
+            self.roles.insert(&role, &members);
+            emit_event("role_grant", json!({
+                "role": role,
+                "account_id": account_id,
+                "sender_id": env::predecessor_account_id()
+            }));
+        }
+    }
+
+    pub(crate) fn internal_revoke_role(&mut self, role: Role, account_id: AccountId) {
+        let mut members = self.internal_get_role_members(role);
+        if members.remove(&account_id) {
+            self.roles.insert(&role, &members);
+            emit_event("role_revoke", json!({
+                "role": role,
+                "account_id": account_id,
+                "sender_id": env::predecessor_account_id()
+            }));
+        }
+    }
+
+    fn assert_role_manager(&self, role: Role) {
+        match role {
+            Role::RoleAdmin => self.assert_owner(),
+            _ => self.assert_role(Role::RoleAdmin)
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use crate::*;
+use crate::events::emit_event;
+use crate::roles::Role;
+use crate::vouch::VouchBurn;
+
+const APPEAL_WINDOW: u64 = 7 * 24 * 60 * 60 * 1_000_000_000;
+const MAX_REASON_LENGTH: usize = 256;
+const MAX_STATEMENT_URL_LENGTH: usize = 512;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum SlashStatus {
+    Active,
+    Appealed,
+    Upheld,
+    Reversed
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SlashRecord {
+    pub account_id: AccountId,
+    pub source_id: AccountId,
+    pub slasher_id: AccountId,
+    pub amount: U128,
+    pub reason: String,
+    pub created_at: U64,
+    pub appeal_deadline: U64,
+    pub statement_url: Option<String>,
+    pub status: SlashStatus,
+    /// burned from the vouchers of the account, refunded if the slash is reversed
+    pub vouch_burns: Vec<VouchBurn>
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Burns up to `amount` of the drip `account_id` earned from `source_id`, returns the slash id.
+    pub fn drip_slash(&mut self, account_id: AccountId, source_id: AccountId, amount: U128, reason: String) -> u64 {
+        self.assert_not_paused(self.pause_flags.burn);
+        let sender_id = env::predecessor_account_id();
+        assert!(self.internal_is_owner_or_role(Role::Slasher, &sender_id) || sender_id == source_id, "not slasher or source");
+        assert!(reason.len() <= MAX_REASON_LENGTH, "reason too long");
+        let amount = std::cmp::min(amount.0, self.internal_get_drip(&account_id, &source_id));
+        assert!(amount > 0, "nothing to slash");
+
+        self.internal_burn(&account_id, amount, &source_id, Some(json!({
+            "contract_id": source_id,
+            "reason": reason
+        }).to_string()));
+
+        let slash_id = self.slashes.len();
+        let vouch_burns = self.internal_slash_vouches(&account_id, slash_id);
+        let slash = SlashRecord {
+            account_id: account_id.clone(),
+            source_id,
+            slasher_id: sender_id,
+            amount: amount.into(),
+            reason,
+            created_at: env::block_timestamp().into(),
+            appeal_deadline: (env::block_timestamp() + APPEAL_WINDOW).into(),
+            statement_url: None,
+            status: SlashStatus::Active,
+            vouch_burns
+        };
+        self.slashes.push(&slash);
+        let mut slash_ids = self.account_slashes.get(&account_id).unwrap_or_default();
+        slash_ids.push(slash_id);
+        self.account_slashes.insert(&account_id, &slash_ids);
+        emit_event("drip_slash", json!({
+            "slash_id": slash_id,
+            "slash": slash
+        }));
+        slash_id
+    }
+
+    pub fn appeal_slash(&mut self, slash_id: u64, statement_url: String) {
+        let mut slash = self.slashes.get(slash_id).expect("slash not found");
+        assert!(env::predecessor_account_id() == slash.account_id, "not slashed account");
+        assert!(slash.status == SlashStatus::Active, "not appealable");
+        assert!(env::block_timestamp() <= slash.appeal_deadline.0, "appeal window closed");
+        assert!(statement_url.len() <= MAX_STATEMENT_URL_LENGTH, "statement url too long");
+
+        slash.statement_url = Some(statement_url.clone());
+        slash.status = SlashStatus::Appealed;
+        self.slashes.replace(slash_id, &slash);
+        emit_event("slash_appeal", json!({
+            "slash_id": slash_id,
+            "account_id": slash.account_id,
+            "statement_url": statement_url
+        }));
+    }
+
+    /// Reversing an appeal re-mints the burned amount to the same source.
+    pub fn resolve_appeal(&mut self, slash_id: u64, uphold: bool) {
+        let sender_id = env::predecessor_account_id();
+        assert!(self.internal_is_owner_or_role(Role::Arbiter, &sender_id), "not arbiter");
+        let mut slash = self.slashes.get(slash_id).expect("slash not found");
+        assert!(slash.status == SlashStatus::Appealed, "not appealed");
+
+        slash.status = match uphold {
+            true => SlashStatus::Upheld,
+            false => {
+                self.assert_not_paused(self.pause_flags.mint);
+                assert!(self.internal_is_source(&slash.source_id), "source revoked");
+                assert!(!self.internal_is_banned(&slash.account_id), "banned");
+                let amount = self.internal_cap_mint(slash.amount.0);
+                if amount > 0 {
+                    self.internal_mint(&slash.account_id, amount, &slash.source_id, json!({
+                        "contract_id": slash.source_id,
+                        "slash_id": slash_id
+                    }).to_string());
+                }
+                self.internal_refund_vouch_burns(&slash.vouch_burns, slash_id);
+                SlashStatus::Reversed
+            }
+        };
+        self.slashes.replace(slash_id, &slash);
+        emit_event("slash_resolve", json!({
+            "slash_id": slash_id,
+            "arbiter_id": sender_id,
+            "status": slash.status
+        }));
+    }
+
+    pub fn get_slash(&self, slash_id: u64) -> Option<SlashRecord> {
+        self.slashes.get(slash_id)
+    }
+
+    pub fn get_slashes(&self, account_id: AccountId) -> Vec<(u64, SlashRecord)> {
+        self.account_slashes.get(&account_id).unwrap_or_default().into_iter()
+            .map(|slash_id| (slash_id, self.slashes.get(slash_id).unwrap()))
+            .collect()
+    }
+}
@@ -0,0 +1,45 @@
+use crate::*;
+
+#[near_bindgen]
+impl StorageManagement for Contract {
+    #[payable]
+    fn storage_deposit(
+        &mut self,
+        account_id: Option<AccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance {
+        self.assert_not_paused(self.pause_flags.storage);
+        let holder_id = account_id.clone().unwrap_or_else(env::predecessor_account_id);
+        let storage_balance = self.token.storage_deposit(account_id, registration_only);
+        self.holders.insert(&holder_id);
+        storage_balance
+    }
+
+    #[payable]
+    fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        self.token.storage_withdraw(amount)
+    }
+
+    #[payable]
+    fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        self.assert_not_paused(self.pause_flags.storage);
+        let account_id = env::predecessor_account_id();
+        let contract_ids = self.internal_get_drip_sources(&account_id);
+        if force.unwrap_or(false) {
+            self.internal_reset_account(&account_id);
+        }
+        let unregistered = self.token.storage_unregister(force);
+        if unregistered {
+            self.internal_remove_holder(&account_id, contract_ids);
+        }
+        unregistered
+    }
+
+    fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        self.token.storage_balance_bounds()
+    }
+
+    fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        self.token.storage_balance_of(account_id)
+    }
+}
@@ -0,0 +1,33 @@
+use crate::*;
+use crate::utils::get_ancestor_ids;
+
+#[near_bindgen]
+impl Contract {
+    /// Drip `account_id` earned from `root_contract_id` and every source nested under it.
+    pub fn ft_balance_of_subtree(&self, account_id: AccountId, root_contract_id: AccountId) -> U128 {
+        self.subtree_balances.get(&(root_contract_id, account_id)).unwrap_or(0).into()
+    }
+
+    pub fn ft_supply_of_subtree(&self, root_contract_id: AccountId) -> U128 {
+        self.subtree_supplies.get(&root_contract_id).unwrap_or(0).into()
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_roll_up(&mut self, account_id: &AccountId, contract_id: &AccountId, old_balance: Balance, new_balance: Balance) {
+        if old_balance == new_balance {
+            return
+        }
+        let update = |value: Balance| (value + new_balance).saturating_sub(old_balance);
+        for root_id in get_ancestor_ids(contract_id) {
+            let key = (root_id.clone(), account_id.clone());
+            let balance = update(self.subtree_balances.get(&key).unwrap_or(0));
+            match balance {
+                0 => self.subtree_balances.remove(&key),
+                _ => self.subtree_balances.insert(&key, &balance)
+            };
+            let supply = update(self.subtree_supplies.get(&root_id).unwrap_or(0));
+            self.subtree_supplies.insert(&root_id, &supply);
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use crate::*;
+use crate::vouch::BPS_DENOMINATOR;
+
+/// What a source requires from collectors the personhood registry does not know as human.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub enum SybilPolicy {
+    RequireHuman,
+    ReducedWeight { weight_bps: u32 }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Queues the change behind the timelock, returns the action id.
+    pub fn set_personhood_registry(&mut self, registry_id: Option<AccountId>) -> u64 {
+        self.internal_queue_action(AdminAction::SetPersonhoodRegistry { registry_id })
+    }
+
+    /// Queues the change behind the timelock, None lets every collector through, returns the action id.
+    pub fn set_sybil_policy(&mut self, contract_id: AccountId, policy: Option<SybilPolicy>) -> u64 {
+        self.internal_queue_action(AdminAction::SetSybilPolicy { contract_id, policy })
+    }
+
+    pub fn get_personhood_registry(&self) -> Option<AccountId> {
+        self.personhood_registry.clone()
+    }
+
+    pub fn get_sybil_policy(&self, contract_id: AccountId) -> Option<SybilPolicy> {
+        self.sybil_policies.get(&contract_id)
+    }
+}
+
+impl Contract {
+    /// Policies only apply while a registry is set.
+    pub(crate) fn internal_needs_human_check(&self, collects: &Vec<AccountId>) -> bool {
+        self.personhood_registry.is_some() && collects.iter().any(|contract_id| self.sybil_policies.get(contract_id).is_some())
+    }
+
+    pub(crate) fn internal_apply_sybil_policy(&self, amount: Balance, contract_id: &AccountId, is_human: bool) -> Balance {
+        if is_human {
+            return amount
+        }
+        match self.sybil_policies.get(contract_id) {
+            Some(SybilPolicy::RequireHuman) => 0,
+            Some(SybilPolicy::ReducedWeight { weight_bps }) => amount * weight_bps as u128 / BPS_DENOMINATOR,
+            None => amount
+        }
+    }
+}
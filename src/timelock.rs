@@ -0,0 +1,155 @@
+use crate::*;
+use crate::events::emit_event;
+use crate::roles::Role;
+use crate::vouch::BPS_DENOMINATOR;
+use crate::revocation::RevocationMode;
+use near_sdk::json_types::Base58CryptoHash;
+
+pub(crate) const DEFAULT_TIMELOCK_DELAY: u64 = 2 * 24 * 60 * 60 * 1_000_000_000;
+
+/// Admin changes partners need advance notice of, applied only after the timelock delay.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub enum AdminAction {
+    RemoveFromWhiteList { contract_id: AccountId },
+    RemoveFromClassWhiteList { class_id: String, contract_id: AccountId },
+    /// de-whitelists the source and starts rolling back what it minted
+    RevokeSource { contract_id: AccountId, mode: RevocationMode, slash_bond: bool },
+    SetClassCap { class_id: Option<String>, supply_cap: Option<U128> },
+    SetVouchSlashBps { vouch_slash_bps: u32 },
+    SetTimelockDelay { delay: U64 },
+    /// deploys the staged code and migrates the state
+    Upgrade { code_hash: Base58CryptoHash },
+    SetPersonhoodRegistry { registry_id: Option<AccountId> },
+    SetSybilPolicy { contract_id: AccountId, policy: Option<SybilPolicy> }
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PendingAction {
+    pub action: AdminAction,
+    pub proposer_id: AccountId,
+    pub queued_at: U64,
+    pub executable_at: U64
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn queue_action(&mut self, action: AdminAction) -> u64 {
+        self.internal_queue_action(action)
+    }
+
+    pub fn cancel_action(&mut self, action_id: u64) {
+        let pending_action = self.pending_actions.get(&action_id).expect("action not found");
+        self.assert_action_role(&pending_action.action);
+        self.pending_actions.remove(&action_id);
+        if let AdminAction::RevokeSource { contract_id, .. } = &pending_action.action {
+            self.internal_lift_suspension(contract_id, action_id);
+        }
+        emit_event("action_cancel", json!({
+            "action_id": action_id,
+            "sender_id": env::predecessor_account_id()
+        }));
+    }
+
+    /// Anyone can execute an action once it is mature.
+    pub fn execute_action(&mut self, action_id: u64) {
+        let pending_action = self.pending_actions.get(&action_id).expect("action not found");
+        assert!(env::block_timestamp() >= pending_action.executable_at.0, "action not mature");
+        self.pending_actions.remove(&action_id);
+        self.internal_execute_action(pending_action.action.clone());
+        emit_event("action_execute", json!({
+            "action_id": action_id,
+            "action": pending_action.action
+        }));
+    }
+
+    pub fn get_pending_actions(&self, from_index: u64, limit: u64) -> Vec<(u64, PendingAction)> {
+        self.pending_actions.iter().skip(from_index as usize).take(limit as usize).collect()
+    }
+
+    pub fn get_timelock_delay(&self) -> U64 {
+        self.timelock_delay.into()
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_queue_action(&mut self, action: AdminAction) -> u64 {
+        self.assert_action_role(&action);
+        self.internal_schedule_action(action, env::predecessor_account_id())
+    }
+
+    pub(crate) fn internal_schedule_action(&mut self, action: AdminAction, proposer_id: AccountId) -> u64 {
+        match &action {
+            AdminAction::SetVouchSlashBps { vouch_slash_bps } => assert!(*vouch_slash_bps as u128 <= BPS_DENOMINATOR, "invalid bps"),
+            AdminAction::SetSybilPolicy { policy: Some(SybilPolicy::ReducedWeight { weight_bps }), .. } => {
+                assert!(*weight_bps as u128 <= BPS_DENOMINATOR, "invalid bps")
+            },
+            AdminAction::RevokeSource { contract_id, .. } => {
+                assert!(!self.suspended_sources.contains_key(contract_id), "revocation queued")
+            },
+            _ => {}
+        }
+        let action_id = self.next_action_id;
+        self.next_action_id += 1;
+        let pending_action = PendingAction {
+            action,
+            proposer_id,
+            queued_at: env::block_timestamp().into(),
+            executable_at: (env::block_timestamp() + self.timelock_delay).into()
+        };
+        self.pending_actions.insert(&action_id, &pending_action);
+        emit_event("action_queue", json!({
+            "action_id": action_id,
+            "pending_action": pending_action
+        }));
+        if let AdminAction::RevokeSource { contract_id, .. } = &pending_action.action {
+            self.internal_suspend_source(contract_id, action_id);
+        }
+        action_id
+    }
+
+    pub(crate) fn internal_execute_action(&mut self, action: AdminAction) {
+        match action {
+            AdminAction::RemoveFromWhiteList { contract_id } => {
+                self.white_list.remove(&contract_id);
+            },
+            AdminAction::RemoveFromClassWhiteList { class_id, contract_id } => {
+                let mut class = self.classes.get(&class_id).expect("class not found");
+                class.white_list.remove(&contract_id);
+                self.classes.insert(&class_id, &class);
+            },
+            AdminAction::RevokeSource { contract_id, mode, slash_bond } => {
+                self.internal_revoke_source(contract_id, mode, slash_bond);
+            },
+            AdminAction::SetClassCap { class_id, supply_cap } => {
+                self.internal_set_class_cap(class_id, supply_cap.map(|cap| cap.0));
+            },
+            AdminAction::SetVouchSlashBps { vouch_slash_bps } => {
+                self.vouch_slash_bps = vouch_slash_bps;
+            },
+            AdminAction::SetTimelockDelay { delay } => {
+                self.timelock_delay = delay.0;
+            },
+            AdminAction::Upgrade { code_hash } => self.internal_deploy_staged_code(code_hash),
+            AdminAction::SetPersonhoodRegistry { registry_id } => {
+                self.personhood_registry = registry_id;
+            },
+            AdminAction::SetSybilPolicy { contract_id, policy } => {
+                match policy {
+                    Some(policy) => self.sybil_policies.insert(&contract_id, &policy),
+                    None => self.sybil_policies.remove(&contract_id)
+                };
+            }
+        }
+    }
+
+    fn assert_action_role(&self, action: &AdminAction) {
+        match action {
+            AdminAction::RemoveFromWhiteList { .. } | AdminAction::RemoveFromClassWhiteList { .. } | AdminAction::RevokeSource { .. } => {
+                self.assert_role(Role::WhitelistManager)
+            },
+            _ => self.assert_owner()
+        }
+    }
+}
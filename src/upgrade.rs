@@ -0,0 +1,137 @@
+use crate::*;
+use crate::events::emit_event;
+use near_sdk::json_types::Base58CryptoHash;
+use near_sdk::CryptoHash;
+use crate::utils::get_ancestor_ids;
+
+/// Bump together with a `migrate` that reads the previous layout whenever `Contract` changes.
+pub(crate) const STATE_VERSION: u32 = 1;
+const MIGRATE_GAS: u64 = 50_000_000_000_000;
+
+/// Layout of the first deployed version, which had no `state_version`.
+#[derive(BorshDeserialize)]
+struct ContractV0 {
+    token: FungibleToken,
+    metadata: LazyOption<FungibleTokenMetadata>,
+    owner_id: AccountId,
+    white_list: HashSet<AccountId>
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ContractVersion {
+    pub code_version: String,
+    pub state_version: u32
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Stages the wasm passed as raw input and queues its deployment, returns the action id.
+    pub fn upgrade(&mut self) -> u64 {
+        self.assert_owner();
+        let code_hash = self.internal_stage_code();
+        self.internal_queue_action(AdminAction::Upgrade { code_hash })
+    }
+
+    /// Stages the wasm passed as raw input for a council `QueueAction` proposal, returns its hash.
+    pub fn stage_upgrade(&mut self) -> Base58CryptoHash {
+        let council = self.council.as_ref().expect("no council");
+        assert!(council.members.contains(&env::predecessor_account_id()), "not council member");
+        self.internal_stage_code()
+    }
+
+    /// Runs in the same batch as the deploy, a panic here rolls the deploy back too.
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let state = env::storage_read(b"STATE").expect("no state");
+        let mut contract = match Contract::try_from_slice(&state) {
+            Ok(contract) => contract,
+            Err(_) => {
+                let old = ContractV0::try_from_slice(&state).expect("unknown state layout");
+                let mut contract = Contract::internal_new(old.token, old.metadata, Some(old.owner_id), old.white_list);
+                contract.state_version = 0;
+                contract
+            }
+        };
+        assert!(contract.state_version <= STATE_VERSION, "cannot downgrade state");
+        let from_version = contract.state_version;
+        contract.state_version = STATE_VERSION;
+        contract.staged_code.remove();
+        emit_event("contract_migrate", json!({
+            "from_version": from_version,
+            "to_version": STATE_VERSION,
+            "code_version": env!("CARGO_PKG_VERSION")
+        }));
+        contract
+    }
+
+    /// Rebuilds the holder sets, leaderboards and subtree totals of accounts that held drip
+    /// before they were tracked, e.g. after migrating from the first version. Safe to repeat.
+    pub fn reindex_accounts(&mut self, account_ids: Vec<AccountId>) {
+        for account_id in account_ids {
+            if self.token.accounts.get(&account_id).is_none() {
+                continue
+            }
+            self.holders.insert(&account_id);
+            let mut subtree_balances: HashMap<AccountId, Balance> = HashMap::new();
+            for contract_id in self.internal_get_drip_sources(&account_id) {
+                let balance = self.internal_get_drip(&account_id, &contract_id);
+                self.internal_update_source_holder(&account_id, &contract_id, balance);
+                self.internal_update_leaderboard(Some(contract_id.clone()), &account_id, balance);
+                for root_id in get_ancestor_ids(&contract_id) {
+                    *subtree_balances.entry(root_id).or_insert(0) += balance;
+                }
+            }
+            let balance = self.ft_balance_of(account_id.clone()).0;
+            self.internal_update_leaderboard(None, &account_id, balance);
+            for (root_id, balance) in subtree_balances {
+                let key = (root_id.clone(), account_id.clone());
+                let old_balance = self.subtree_balances.get(&key).unwrap_or(0);
+                match balance {
+                    0 => self.subtree_balances.remove(&key),
+                    _ => self.subtree_balances.insert(&key, &balance)
+                };
+                let supply = (self.subtree_supplies.get(&root_id).unwrap_or(0) + balance).saturating_sub(old_balance);
+                self.subtree_supplies.insert(&root_id, &supply);
+            }
+        }
+    }
+
+    pub fn get_version(&self) -> ContractVersion {
+        ContractVersion {
+            code_version: env!("CARGO_PKG_VERSION").to_string(),
+            state_version: self.state_version
+        }
+    }
+
+    pub fn get_staged_code_hash(&self) -> Option<Base58CryptoHash> {
+        self.staged_code.get().map(|code| internal_code_hash(&code))
+    }
+}
+
+fn internal_code_hash(code: &[u8]) -> Base58CryptoHash {
+    let hash: CryptoHash = env::sha256(code).try_into().unwrap();
+    hash.into()
+}
+
+impl Contract {
+    fn internal_stage_code(&mut self) -> Base58CryptoHash {
+        let code = env::input().expect("no code");
+        let code_hash = internal_code_hash(&code);
+        self.staged_code.set(&code);
+        emit_event("upgrade_stage", json!({
+            "code_hash": code_hash,
+            "sender_id": env::predecessor_account_id()
+        }));
+        code_hash
+    }
+
+    pub(crate) fn internal_deploy_staged_code(&mut self, code_hash: Base58CryptoHash) {
+        let code = self.staged_code.get().expect("no staged code");
+        assert!(internal_code_hash(&code) == code_hash, "staged code changed");
+        Promise::new(env::current_account_id())
+            .deploy_contract(code)
+            .function_call("migrate".to_string(), vec![], 0, Gas(MIGRATE_GAS));
+    }
+}
@@ -9,3 +9,49 @@ pub(crate) fn get_root_id(contract_id: AccountId) -> AccountId {
     let root_id = arr.get(arr.len() - 2).unwrap().clone() + "." + arr.get(arr.len() - 1).unwrap();
     AccountId::try_from(root_id).unwrap()
 }
+
+/// The account one level up, e.g. `popula.near` for `club.popula.near`, None for top-level accounts.
+pub(crate) fn get_parent_id(contract_id: &AccountId) -> Option<AccountId> {
+    contract_id.as_str().split_once('.').map(|(_, parent_id)| AccountId::try_from(parent_id.to_string()).unwrap())
+}
+
+pub(crate) fn get_storage_key(prefix: &[u8], account_id: &AccountId) -> Vec<u8> {
+    [prefix, &env::sha256(account_id.as_bytes())].concat()
+}
+
+pub(crate) fn get_pair_storage_key(prefix: &[u8], account_id: &AccountId, contract_id: &AccountId) -> Vec<u8> {
+    [prefix, &env::sha256(account_id.as_bytes()), &env::sha256(contract_id.as_bytes())].concat()
+}
+
+pub(crate) fn integer_sqrt(value: u128) -> u128 {
+    if value < 2 {
+        return value
+    }
+    let mut x = value;
+    let mut y = (x + 1) / 2;
+    while y < x {
+        x = y;
+        y = (x + value / x) / 2;
+    }
+    x
+}
+
+/// The account itself followed by each parent down to its root, e.g. `channel.club.popula.near`,
+/// `club.popula.near`, `popula.near`.
+pub(crate) fn get_ancestor_ids(contract_id: &AccountId) -> Vec<AccountId> {
+    let arr: Vec<&str> = contract_id.as_str().split('.').collect();
+    (0..arr.len().saturating_sub(1))
+        .map(|i| AccountId::try_from(arr[i..].join(".")).unwrap())
+        .collect()
+}
+
+/// Keeps the part of the attached deposit paying for storage added since `initial_storage_usage` and refunds the rest.
+pub(crate) fn refund_deposit(initial_storage_usage: u64) {
+    let storage_cost = env::storage_usage().saturating_sub(initial_storage_usage) as u128 * env::storage_byte_cost();
+    let attached_deposit = env::attached_deposit();
+    assert!(attached_deposit >= storage_cost, "not enough deposit");
+    let refund = attached_deposit - storage_cost;
+    if refund > 0 {
+        Promise::new(env::predecessor_account_id()).transfer(refund);
+    }
+}
@@ -1,8 +1,24 @@
 use std::{collections::HashMap};
 
 use crate::*;
+use crate::checkpoint::get_checkpoint_balance;
 
 #[near_bindgen]
 impl Contract {
+    pub fn ft_balance_of_at(&self, account_id: AccountId, timestamp: U64) -> U128 {
+        let balance = self.account_checkpoints.get(&account_id).and_then(|checkpoints| {
+            get_checkpoint_balance(&checkpoints, timestamp.0)
+        });
+        match balance {
+            Some(balance) => balance.into(),
+            None => self.ft_balance_of(account_id)
+        }
+    }
 
-}
\ No newline at end of file
+    pub fn ft_total_supply_at(&self, timestamp: U64) -> U128 {
+        match get_checkpoint_balance(&self.supply_checkpoints, timestamp.0) {
+            Some(balance) => balance.into(),
+            None => self.ft_total_supply()
+        }
+    }
+}
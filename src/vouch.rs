@@ -0,0 +1,198 @@
+use crate::*;
+use crate::events::emit_event;
+
+pub(crate) const BPS_DENOMINATOR: u128 = 10_000;
+/// keeps `drip_slash`, which walks every vouch of the slashed account, within gas
+const MAX_VOUCHES_PER_ACCOUNT: usize = 32;
+const MIN_VOUCH_AMOUNT: Balance = 1_000_000_000_000_000_000_000_000;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Vouch {
+    pub voucher_id: AccountId,
+    pub account_id: AccountId,
+    pub amount: U128,
+    pub lock_id: u64,
+    pub expires_at: U64
+}
+
+/// Drip burned from a voucher by a slash, kept so a reversed slash can give it back.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct VouchBurn {
+    pub vouch_id: u64,
+    pub voucher_id: AccountId,
+    pub source_id: AccountId,
+    pub amount: U128
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Locks `amount` of the caller's drip for `duration` nanoseconds in favour of `for_account`.
+    /// The attached deposit pays for the vouch's storage, the rest is refunded.
+    #[payable]
+    pub fn vouch(&mut self, for_account: AccountId, amount: U128, duration: U64) -> u64 {
+        assert!(env::attached_deposit() > 0, "requires attached deposit");
+        let initial_storage_usage = env::storage_usage();
+        let sender_id = env::predecessor_account_id();
+        assert!(sender_id != for_account, "can't vouch for self");
+        assert!(amount.0 >= MIN_VOUCH_AMOUNT, "amount too small");
+        self.internal_assert_available(&sender_id, amount.0);
+
+        let mut vouch_ids = self.account_vouches.get(&for_account).unwrap_or_default();
+        assert!(vouch_ids.len() < MAX_VOUCHES_PER_ACCOUNT, "too many vouches");
+
+        let expires_at: U64 = (env::block_timestamp() + duration.0).into();
+        let lock_id = self.internal_create_lock(sender_id.clone(), env::current_account_id(), amount.0, expires_at);
+        let vouch_id = self.next_vouch_id;
+        self.next_vouch_id += 1;
+        let vouch = Vouch {
+            voucher_id: sender_id,
+            account_id: for_account.clone(),
+            amount,
+            lock_id,
+            expires_at
+        };
+        self.vouches.insert(&vouch_id, &vouch);
+        self.lock_vouches.insert(&lock_id, &vouch_id);
+        vouch_ids.push(vouch_id);
+        self.account_vouches.insert(&for_account, &vouch_ids);
+        self.internal_update_vouched_score(&for_account, amount.0, true);
+        emit_event("vouch_create", json!({
+            "vouch_id": vouch_id,
+            "vouch": vouch
+        }));
+        refund_deposit(initial_storage_usage);
+        vouch_id
+    }
+
+    pub fn release_vouch(&mut self, vouch_id: u64) {
+        let vouch = self.vouches.get(&vouch_id).expect("vouch not found");
+        assert!(env::predecessor_account_id() == vouch.voucher_id, "not voucher");
+        assert!(env::block_timestamp() >= vouch.expires_at.0, "still locked");
+        self.internal_remove_vouch(vouch_id);
+    }
+
+    /// Share of each vouch, in basis points, slashed when the vouched account is slashed.
+    /// Queues the change behind the timelock, returns the action id.
+    pub fn set_vouch_slash_bps(&mut self, vouch_slash_bps: u32) -> u64 {
+        self.internal_queue_action(AdminAction::SetVouchSlashBps { vouch_slash_bps })
+    }
+
+    pub fn get_vouch_slash_bps(&self) -> u32 {
+        self.vouch_slash_bps
+    }
+
+    pub fn vouched_score(&self, account_id: AccountId) -> U128 {
+        self.vouched_scores.get(&account_id).unwrap_or(0).into()
+    }
+
+    pub fn get_vouches(&self, account_id: AccountId) -> Vec<(u64, Vouch)> {
+        self.account_vouches.get(&account_id).unwrap_or_default().into_iter()
+            .map(|vouch_id| (vouch_id, self.vouches.get(&vouch_id).unwrap()))
+            .collect()
+    }
+}
+
+impl Contract {
+    /// Drops the vouch and its lock, also used when the voucher is force-unregistered.
+    pub(crate) fn internal_remove_vouch(&mut self, vouch_id: u64) {
+        let vouch = self.vouches.remove(&vouch_id).expect("vouch not found");
+        self.lock_vouches.remove(&vouch.lock_id);
+        let mut vouch_ids = self.account_vouches.get(&vouch.account_id).unwrap_or_default();
+        vouch_ids.retain(|id| *id != vouch_id);
+        self.account_vouches.insert(&vouch.account_id, &vouch_ids);
+        self.internal_update_vouched_score(&vouch.account_id, vouch.amount.0, false);
+        if self.locks.get(&vouch.lock_id).is_some() {
+            self.internal_remove_lock(vouch.lock_id);
+        }
+        emit_event("vouch_release", json!({
+            "vouch_id": vouch_id,
+            "voucher_id": vouch.voucher_id,
+            "account_id": vouch.account_id,
+            "amount": vouch.amount
+        }));
+    }
+
+    pub(crate) fn internal_slash_vouches(&mut self, account_id: &AccountId, slash_id: u64) -> Vec<VouchBurn> {
+        let mut vouch_burns = vec![];
+        for vouch_id in self.account_vouches.get(account_id).unwrap_or_default() {
+            let vouch = self.vouches.get(&vouch_id).unwrap();
+            let lock = match self.locks.get(&vouch.lock_id) {
+                Some(lock) => lock,
+                None => continue
+            };
+            let amount = std::cmp::min(
+                std::cmp::min(vouch.amount.0 * self.vouch_slash_bps as u128 / BPS_DENOMINATOR, lock.amount.0),
+                self.ft_balance_of(vouch.voucher_id.clone()).0
+            );
+            if amount == 0 {
+                continue
+            }
+            // shrink the lock first so the burn doesn't see it as locked drip going missing
+            self.internal_reduce_vouch(vouch_id, amount);
+            let contract_ids = self.internal_get_drip_sources(&vouch.voucher_id);
+            let burned = self.internal_burn_from_sources(&vouch.voucher_id, contract_ids, amount, json!({
+                "vouch_id": vouch_id,
+                "slash_id": slash_id
+            }));
+            vouch_burns.extend(burned.into_iter().map(|(source_id, amount)| VouchBurn {
+                vouch_id,
+                voucher_id: vouch.voucher_id.clone(),
+                source_id,
+                amount: amount.into()
+            }));
+            emit_event("vouch_slash", json!({
+                "vouch_id": vouch_id,
+                "slash_id": slash_id,
+                "voucher_id": vouch.voucher_id,
+                "amount": U128(amount)
+            }));
+        }
+        vouch_burns
+    }
+
+    /// Re-mints what a reversed slash burned from vouchers and restores vouches that are still active.
+    /// Burns from revoked sources, banned vouchers or beyond the supply cap are not re-minted.
+    pub(crate) fn internal_refund_vouch_burns(&mut self, vouch_burns: &Vec<VouchBurn>, slash_id: u64) {
+        for vouch_burn in vouch_burns {
+            if !self.internal_is_source(&vouch_burn.source_id) || self.internal_is_banned(&vouch_burn.voucher_id) {
+                continue
+            }
+            let amount = self.internal_cap_mint(vouch_burn.amount.0);
+            if amount == 0 {
+                continue
+            }
+            self.internal_mint(&vouch_burn.voucher_id, amount, &vouch_burn.source_id, json!({
+                "contract_id": vouch_burn.source_id,
+                "vouch_id": vouch_burn.vouch_id,
+                "slash_id": slash_id
+            }).to_string());
+            let mut vouch = match self.vouches.get(&vouch_burn.vouch_id) {
+                Some(vouch) if self.locks.get(&vouch.lock_id).is_some() => vouch,
+                _ => continue
+            };
+            vouch.amount = (vouch.amount.0 + amount).into();
+            self.vouches.insert(&vouch_burn.vouch_id, &vouch);
+            self.internal_extend_lock(vouch.lock_id, amount);
+            self.internal_update_vouched_score(&vouch.account_id, amount, true);
+        }
+    }
+
+    pub(crate) fn internal_reduce_vouch(&mut self, vouch_id: u64, amount: Balance) {
+        let mut vouch = self.vouches.get(&vouch_id).expect("vouch not found");
+        self.internal_reduce_lock(vouch.lock_id, amount);
+        vouch.amount = (vouch.amount.0 - amount).into();
+        self.vouches.insert(&vouch_id, &vouch);
+        self.internal_update_vouched_score(&vouch.account_id, amount, false);
+    }
+
+    fn internal_update_vouched_score(&mut self, account_id: &AccountId, amount: Balance, increase: bool) {
+        let score = self.vouched_scores.get(account_id).unwrap_or(0);
+        let score = match increase {
+            true => score + amount,
+            false => score - amount
+        };
+        self.vouched_scores.insert(account_id, &score);
+    }
+}